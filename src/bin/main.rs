@@ -1,33 +1,101 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
+use clap::Command;
 
 use git_helpe_rs::{
     autocompletion,
     branch::{checkout_to_branch_with_prefix, checkout_to_branch_with_template},
     cli,
     commit::commit_with_formatted_message,
+    conventional::conventional_commit,
+    file_utils::config_file::get_path_to_config,
     git_config::GitConfig,
+    hooks::{install_hooks, run_hook},
+    template::templates_help,
 };
 
+/// Lists stored templates in `c --help` and `bt --help` and types in `cc --help`,
+/// help is still shown when config can't be read
+fn with_templates_help(cli: Command) -> Command {
+    // Config is read again only when help is asked for
+    if !std::env::args().any(|arg| arg == "-h" || arg == "--help") {
+        return cli;
+    }
+    let path_to_config = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .filter(|path| path.is_file());
+    let Ok(config) = GitConfig::from_file(get_path_to_config(path_to_config)) else {
+        return cli;
+    };
+
+    let add_help = |command: Command, help: String| {
+        let help = match command.get_after_help() {
+            Some(after_help) => format!("{}\n{}", after_help, help),
+            None => help,
+        };
+        command.after_help(help)
+    };
+    cli.mut_subcommand("c", |command| {
+        add_help(
+            command,
+            templates_help("Commit templates", &config.data.commit_template_variants),
+        )
+    })
+    .mut_subcommand("bt", |command| {
+        add_help(
+            command,
+            templates_help("Branch templates", &config.data.branch_template_variants),
+        )
+    })
+    .mut_subcommand("cc", |command| {
+        add_help(
+            command,
+            format!(
+                "Allowed types: {}",
+                config.data.conventional.types.join(", ")
+            ),
+        )
+    })
+}
+
 fn main() -> Result<()> {
-    let args: cli::ParsedArguments = cli::define::build_cli_commands().get_matches().try_into()?;
+    let args: cli::ParsedArguments = with_templates_help(cli::define::build_cli_commands())
+        .get_matches()
+        .try_into()?;
 
-    let mut config = GitConfig::from_file(args.path_to_config);
+    // Broken config can't be read, so it's repaired before reading it
+    if let cli::OperationWithArguments::RepairConfig(repair_args) = &args.operation_with_arguments {
+        return GitConfig::repair(&args.path_to_config, repair_args);
+    }
+    // Hook has to exit with non-zero code, so git stops the commit,
+    // and reads config itself, so broken one doesn't block every commit
+    if let cli::OperationWithArguments::Hook(command) = args.operation_with_arguments {
+        return run_hook(command, &args.path_to_config);
+    }
+
+    let mut config = GitConfig::from_file(args.path_to_config)?;
 
     let resp = match args.operation_with_arguments {
         cli::OperationWithArguments::BranchFromClipboard(val) => {
             checkout_to_branch_with_prefix(val, config)
         }
         cli::OperationWithArguments::Commit(val) => commit_with_formatted_message(val, config),
+        cli::OperationWithArguments::ConventionalCommit(args) => conventional_commit(args, config),
         cli::OperationWithArguments::SetBranchPrefix(args) => {
             config.set_branch_prefix_variant(args.key, args.value)
         }
-        cli::OperationWithArguments::Show => {
-            let config_to_display = config.display_config()?;
+        cli::OperationWithArguments::Show(args) => {
+            let config_to_display = if args.origin {
+                config.display_config_origins()
+            } else {
+                config.display_config()?
+            };
             println!("{}", config_to_display);
             Ok(())
         }
-        // TODO implement delete
-        // cli::OperationWithArguments::Delete(val) => config.delete_branch_prefix_variant(val.key),
+        cli::OperationWithArguments::Section(command) => config.run_section_command(command),
         cli::OperationWithArguments::SetBranchFormat(args) => {
             config.set_branch_template_variant(args)
         }
@@ -40,6 +108,35 @@ fn main() -> Result<()> {
         cli::OperationWithArguments::SetClipboardCommands(args) => {
             config.set_clipboard_command(args)
         }
+        cli::OperationWithArguments::SetAutoComplete(values) => {
+            config.set_autocomplete_values(values)
+        }
+        cli::OperationWithArguments::SetBranchPattern(pattern) => {
+            config.set_branch_pattern(pattern)
+        }
+        cli::OperationWithArguments::SetBranchNameMode(mode) => config.set_branch_name_mode(mode),
+        cli::OperationWithArguments::SetExistingBranch(action) => {
+            config.set_existing_branch(action)
+        }
+        cli::OperationWithArguments::SetBodyWidth(width) => config.set_commit_body_width(width),
+        cli::OperationWithArguments::SetConventionalTypes(types) => {
+            config.set_conventional_types(types)
+        }
+        cli::OperationWithArguments::SetSubjectMaxLength(length) => {
+            config.set_subject_max_length(length)
+        }
+        cli::OperationWithArguments::Pair(command) => config.run_pair_command(command),
+        cli::OperationWithArguments::Hook(_) => Ok(()),
+        cli::OperationWithArguments::InstallHooks(args) => install_hooks(args),
+        cli::OperationWithArguments::SetTicketKeys(ticket_keys) => {
+            config.set_ticket_keys(ticket_keys)
+        }
+        cli::OperationWithArguments::SetTicketFromClipboard(from_clipboard) => {
+            config.set_ticket_from_clipboard(from_clipboard)
+        }
+        cli::OperationWithArguments::ConvertConfig(args) => config.convert(args),
+        cli::OperationWithArguments::RepairConfig(_) => Ok(()),
+        cli::OperationWithArguments::MigrateConfig(args) => config.migrate(args),
         cli::OperationWithArguments::GenerateAutocompletionScript(path) => {
             autocompletion::generate(path)
         }
@@ -1,33 +1,36 @@
 use std::process::Command;
 
 use anyhow::{anyhow, Error, Result};
-use regex::Regex;
 
 use crate::{
-    cli::{CheckoutToPrefix, DryRunAndCopyFlag, UseTemplate},
+    branch_name::valid_branch_name,
+    cli::{CheckoutToPrefix, ConfigSection, DryRunAndCopyFlag, UseTemplate},
+    clipboard::{default_recognizers, recognize},
+    config_sections::get_entry,
+    existing_branch::resolve_checkout,
     git_config::GitConfig,
-    run_mode::{get_run_mode_from_options, run_copy, RunMode},
-    template::{interpolate, validate_interpolation_places_count},
+    interactive::{stdin_is_terminal, Prompter},
+    run_mode::{command_line, get_run_mode_from_options, run_command, run_copy, RunMode},
+    template::{InterpolationValues, Template},
+    ticket::ticket_patterns,
 };
 
 pub fn checkout_to_branch_with_prefix(options: CheckoutToPrefix, config: GitConfig) -> Result<()> {
-    let checkout_regex = Regex::new(r"^git checkout -b [a-zA-Z0-9_.-]+$").unwrap();
     let paste_command = &config.data.clipboard_commands.paste;
 
-    let clipboard_value = Command::new(paste_command)
-        .output()
-        .expect("Couldn't run paste_command");
+    let clipboard_value = Command::new(paste_command).output().map_err(|err| {
+        anyhow!(
+            "Couldn't run paste command `{}`: {} \n \
+            Set the one your system has with set-clipboard-command",
+            paste_command,
+            err
+        )
+    })?;
 
-    let output_as_string = String::from_utf8(clipboard_value.stdout).unwrap();
+    let output_as_string = String::from_utf8_lossy(&clipboard_value.stdout);
+    let patterns = ticket_patterns(config.data.ticket_keys.as_deref().unwrap_or_default())?;
+    let recognized = recognize(&output_as_string, &default_recognizers(patterns))?;
 
-    if !checkout_regex.is_match(&output_as_string) {
-        return Err(anyhow!(
-            "What you have in your clipboard is not a valid git checkout command \n
-        valid one looks like this: \n
-        git checkout -b name-of-your-branch
-        "
-        ));
-    }
     let prefix_found = match config.data.branch_prefix_variants.get(&options.prefix_key) {
         None => {
             return Err(anyhow!(
@@ -38,88 +41,122 @@ pub fn checkout_to_branch_with_prefix(options: CheckoutToPrefix, config: GitConf
         Some(prefix) => prefix,
     };
 
-    let split_on_space: Vec<String> = output_as_string.split(" ").map(|s| s.to_string()).collect();
-
-    let after_prefix = &split_on_space[3..].join("");
-
-    let full_branch_name = prefix_found.to_owned() + after_prefix;
-
+    match &recognized.ticket {
+        Some(ticket) => println!(
+            "Branch name from clipboard: {} (ticket {})",
+            recognized.name, ticket
+        ),
+        None => println!("Branch name from clipboard: {}", recognized.name),
+    }
+    let full_branch_name =
+        branch_name_for_git(&(prefix_found.to_owned() + &recognized.name), &config)?;
     let run_mode = get_run_mode_from_options(DryRunAndCopyFlag {
         dry_run: options.dry_run,
         copy: options.copy,
     });
+    // Nothing runs in dry run or copy, so there is nothing to ask about
+    let is_normal = matches!(run_mode, RunMode::Normal);
+    let mut prompter = Prompter::stdio();
+    let Some(checkout) = resolve_checkout(
+        &full_branch_name,
+        config.data.existing_branch,
+        is_normal.then_some(&mut prompter),
+    )?
+    else {
+        if is_normal {
+            println!("Aborted");
+        }
+        return Ok(());
+    };
+    let git_command = command_line("git", &checkout.git_args());
 
-    return match run_mode {
+    match run_mode {
         RunMode::Normal => {
-            let result = Command::new("git")
-                .arg("checkout")
-                .arg("-b")
-                .arg(full_branch_name)
-                .output()
-                .unwrap();
-
-            println!("git output: \n {:?}", String::from_utf8(result.stdout));
+            let output = run_command("git", &checkout.git_args())?;
+
+            println!("git output: \n {}", output);
             Ok(())
         }
         RunMode::DryRun => {
-            println!(
-                "Going to run: \n \
-                    git checkout -b {}",
-                full_branch_name
-            );
+            println!("Going to run: \n {}", git_command);
             Ok(())
         }
-        RunMode::Copy => run_copy(&config, format!("git checkout -b {}", full_branch_name)),
+        RunMode::Copy => run_copy(&config, git_command),
         RunMode::DryRunAndCopy => {
             let copy_command = config.data.clipboard_commands.copy;
 
             println!(
                 "Going to run: \n \
-        echo 'git checkout -b {}' > {}",
-                full_branch_name, copy_command
+        echo '{}' > {}",
+                git_command, copy_command
             );
             Ok(())
         }
-    };
+    }
+}
+
+/// Name checked against git's rules, note is shown when it had to be fixed
+fn branch_name_for_git(name: &str, config: &GitConfig) -> Result<String> {
+    let valid_name = valid_branch_name(name, config.data.branch_name_mode)?;
+    if valid_name != name {
+        println!(
+            "`{}` isn't a valid branch name, using `{}`",
+            name, valid_name
+        );
+    }
+    Ok(valid_name)
+}
+
+/// Values without filters have spaces replaced, so they make a valid branch name
+fn branch_default_filter(value: &str) -> String {
+    value.replace(' ', "-")
 }
 
 pub fn checkout_to_branch_with_template(
     options: UseTemplate,
     config: GitConfig,
 ) -> Result<(), Error> {
-    let selected_branch_format = options.key;
-
-    let picked_branch_format = config
-        .data
-        .branch_template_variants
-        .get(&selected_branch_format)
-        .unwrap_or_else(|| {
-            panic!(
-                "No branch template under given key {} \n \
-       You should add it prior to trying to use 
-        ",
-                selected_branch_format
-            )
-        });
-
-    let is_valid =
-        validate_interpolation_places_count(picked_branch_format, options.interpolate_values.len());
-
-    if is_valid.is_err() {
-        let err: Error = is_valid.err().unwrap();
-        return Err(err);
+    let mut prompter = Prompter::stdio();
+    let selected_branch_format = if options.pick_key {
+        prompter.pick_key("Branch templates", &config.data.branch_template_variants)?
+    } else {
+        options.key
     };
 
-    let interpolate_values = options
-        .interpolate_values
-        .iter()
-        .map(|val| val.replace(" ", "-"))
-        .collect();
+    let picked_branch_format = get_entry(
+        &ConfigSection::BranchTemplates,
+        &config.data.branch_template_variants,
+        &selected_branch_format,
+    )?;
+
+    // Body and trailers are only used by commit templates
+    let template = Template::parse(&picked_branch_format.template)?;
+
+    let mut interpolate_values: InterpolationValues = options.interpolate_values.into();
+    interpolate_values.context = template.resolve_context(&config.data);
+
+    let interactive = options.interactive
+        || (template.validate(&interpolate_values).is_err() && stdin_is_terminal());
+    if interactive {
+        prompter.fill_values(
+            picked_branch_format,
+            &template,
+            &mut interpolate_values,
+            &|values| template.render_with_default_filter(values, branch_default_filter),
+        )?;
+    }
+
+    template.validate(&interpolate_values)?;
+    picked_branch_format.validate_values(&interpolate_values)?;
 
     // Had to figure out around closure that couldn't move value
-    let interpolate_values_for_debugging = format!("{:?}", interpolate_values);
-    let interpolated_branch =
-        interpolate(picked_branch_format, interpolate_values).unwrap_or_else(|_err| {
+    let interpolate_values_for_debugging = format!(
+        "{:?} {:?}",
+        interpolate_values.positional, interpolate_values.named
+    );
+    let interpolated_branch = template
+        .render_with_default_filter(&interpolate_values, branch_default_filter)
+        .unwrap_or_else(|_err| {
             panic!(
                 "Couldn't interpolate branch format \n \
                 Trying to interpolate template: \n \
@@ -127,48 +164,56 @@ pub fn checkout_to_branch_with_template(
                 with: \n \
                 {:?}
                 ",
-                picked_branch_format, interpolate_values_for_debugging
+                picked_branch_format.template, interpolate_values_for_debugging
             )
         });
-
+    let interpolated_branch = branch_name_for_git(&interpolated_branch, &config)?;
+    let existing_branch = picked_branch_format
+        .existing_branch
+        .unwrap_or(config.data.existing_branch);
     let run_mode = get_run_mode_from_options(DryRunAndCopyFlag {
         dry_run: options.dry_run,
         copy: options.copy,
     });
+    // Nothing runs in dry run or copy, so there is nothing to ask about
+    let is_normal = matches!(run_mode, RunMode::Normal);
+    let Some(checkout) = resolve_checkout(
+        &interpolated_branch,
+        existing_branch,
+        is_normal.then_some(&mut prompter),
+    )?
+    else {
+        if is_normal {
+            println!("Aborted");
+        }
+        return Ok(());
+    };
+    let git_command = command_line("git", &checkout.git_args());
 
     match run_mode {
         RunMode::Normal => {
-            let output = Command::new("git")
-                .arg("checkout")
-                .arg("-b")
-                .arg(interpolated_branch)
-                .output()
-                .unwrap()
-                .stdout;
-
-            println!("{}", String::from_utf8_lossy(&output));
+            if interactive && !prompter.confirm(&format!("Run {}?", git_command))? {
+                println!("Aborted");
+                return Ok(());
+            }
+            let output = run_command("git", &checkout.git_args())?;
+
+            println!("{}", output);
             Ok(())
         }
         RunMode::DryRun => {
-            let command_to_print = format!("git checkout -b {}", interpolated_branch);
-            println!("Command to be executed: \n {}", command_to_print);
+            println!("Command to be executed: \n {}", git_command);
             Ok(())
         }
         RunMode::DryRunAndCopy => {
             let copy_command = config.data.clipboard_commands.copy;
 
-            let command_to_print = format!(
-                "echo 'git checkout -b {}' > {}",
-                interpolated_branch, copy_command
-            );
+            let command_to_print = format!("echo '{}' > {}", git_command, copy_command);
             let message_to_print =
                 format!("command that's going to be run: \n {}", command_to_print);
             println!("{}", message_to_print);
             Ok(())
         }
-        RunMode::Copy => {
-            let value_to_copy = format!("git checkout -b {}", interpolated_branch);
-            run_copy(&config, value_to_copy)
-        }
+        RunMode::Copy => run_copy(&config, git_command),
     }
 }
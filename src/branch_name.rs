@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Error, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Named group of branch pattern that's used for `{b}`
+const BRANCH_NUMBER_GROUP: &str = "b";
+
+pub fn compile_branch_pattern(pattern: &str) -> Result<Regex> {
+    let regex = Regex::new(pattern)?;
+    if regex.capture_names().flatten().next().is_none() {
+        return Err(anyhow!(
+            "Branch pattern {} has no named groups \n \
+            Name what you want to capture, e.g. (?P<ticket>[A-Z]+-\\d+)",
+            pattern
+        ));
+    }
+    Ok(regex)
+}
+
+/// Values of named groups of branch pattern matched against branch name
+pub fn get_branch_captures(branch_name: &str, pattern: &Regex) -> HashMap<String, String> {
+    let mut captures_by_name = HashMap::new();
+
+    if let Some(captures) = pattern.captures(branch_name) {
+        for name in pattern.capture_names().flatten() {
+            if let Some(value) = captures.name(name) {
+                captures_by_name.insert(name.to_owned(), value.as_str().to_owned());
+            }
+        }
+    }
+
+    captures_by_name
+}
+
+/// Uses `b` group of branch pattern when there is one,
+/// otherwise takes the longest run of digits in branch name
+pub fn get_branch_number_from_branch_name(
+    branch_name: &str,
+    pattern: Option<&Regex>,
+) -> Result<String, Error> {
+    if let Some(pattern) = pattern {
+        if pattern
+            .capture_names()
+            .flatten()
+            .any(|name| name == BRANCH_NUMBER_GROUP)
+        {
+            return get_branch_captures(branch_name, pattern)
+                .remove(BRANCH_NUMBER_GROUP)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Branch {} doesn't match branch pattern {}",
+                        branch_name,
+                        pattern
+                    )
+                });
+        }
+    }
+
+    let branch_number =
+        branch_name
+            .split(|c: char| !c.is_ascii_digit())
+            .fold("", |longest, digits| {
+                if digits.len() > longest.len() {
+                    digits
+                } else {
+                    longest
+                }
+            });
+
+    if branch_number.is_empty() {
+        return Err(Error::msg("There is no number in branch name"));
+    }
+
+    Ok(branch_number.to_owned())
+}
+
+/// What happens to branch names git would reject
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BranchNameMode {
+    /// Invalid characters are replaced with `-`, invalid parts dropped
+    #[default]
+    Sanitize,
+    /// Invalid names are refused
+    Strict,
+}
+
+impl TryFrom<&str> for BranchNameMode {
+    type Error = Error;
+    fn try_from(mode: &str) -> Result<Self> {
+        match mode {
+            "sanitize" => Ok(BranchNameMode::Sanitize),
+            "strict" => Ok(BranchNameMode::Strict),
+            _ => Err(anyhow!(
+                "Unknown branch name mode {}, use sanitize or strict",
+                mode
+            )),
+        }
+    }
+}
+
+/// Characters git never allows in ref names, besides control ones
+const FORBIDDEN_CHARACTERS: &str = " ~^:?*[\\";
+
+fn is_forbidden(character: char) -> bool {
+    character.is_ascii_control() || FORBIDDEN_CHARACTERS.contains(character)
+}
+
+/// Why `git check-ref-format --branch` would reject the name, empty when it wouldn't
+pub fn branch_name_problems(name: &str) -> Vec<String> {
+    if name.is_empty() {
+        return vec!["is empty".to_owned()];
+    }
+    let mut problems = vec![];
+
+    let mut forbidden: Vec<String> = vec![];
+    for character in name.chars().filter(|character| is_forbidden(*character)) {
+        let described = match character {
+            ' ' => "space".to_owned(),
+            character if character.is_ascii_control() => {
+                format!("control character {:?}", character)
+            }
+            character => format!("`{}`", character),
+        };
+        if !forbidden.contains(&described) {
+            forbidden.push(described);
+        }
+    }
+    if !forbidden.is_empty() {
+        problems.push(format!("contains {}", forbidden.join(", ")));
+    }
+
+    for sequence in ["..", "@{", "//"] {
+        if name.contains(sequence) {
+            problems.push(format!("contains `{}`", sequence));
+        }
+    }
+    if name == "@" || name == "HEAD" {
+        problems.push(format!("can't be `{}`", name));
+    }
+    if name.starts_with('-') {
+        problems.push("starts with `-`".to_owned());
+    }
+    if name.starts_with('/') || name.ends_with('/') {
+        problems.push("starts or ends with `/`".to_owned());
+    }
+    if name.ends_with('.') {
+        problems.push("ends with `.`".to_owned());
+    }
+    if name.split('/').any(|part| part.starts_with('.')) {
+        problems.push("has part starting with `.`".to_owned());
+    }
+    if name.split('/').any(|part| part.ends_with(".lock")) {
+        problems.push("has part ending with `.lock`".to_owned());
+    }
+    problems
+}
+
+/// Fixes what it can until nothing changes, as fixing one rule can break another,
+/// e.g. dropping `.lock` from `x.lock.` leaves `x.`
+pub fn sanitize_branch_name(name: &str) -> String {
+    // Dashes around are taken in, so `fix: login` becomes `fix-login`
+    let forbidden_regex = Regex::new(r"-*[\x00-\x20\x7f~^:?*\[\\]+-*").unwrap();
+    let mut sanitized = forbidden_regex.replace_all(name, "-").into_owned();
+    loop {
+        let previous = sanitized.clone();
+        sanitized = sanitized.replace("@{", "-{").replace("..", ".");
+        sanitized = sanitized
+            .split('/')
+            .map(|part| {
+                let mut part = part.trim_start_matches('.');
+                while let Some(stripped) = part.strip_suffix(".lock") {
+                    part = stripped;
+                }
+                // Left by characters replaced at the ends
+                part.trim_matches('-')
+            })
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join("/");
+        sanitized = sanitized.trim_end_matches('.').to_owned();
+        if sanitized == previous {
+            return sanitized;
+        }
+    }
+}
+
+/// Branch name git accepts, fixed or refused depending on mode
+pub fn valid_branch_name(name: &str, mode: BranchNameMode) -> Result<String> {
+    let problems = branch_name_problems(name);
+    if problems.is_empty() {
+        return Ok(name.to_owned());
+    }
+    if mode == BranchNameMode::Strict {
+        return Err(anyhow!(
+            "Branch name `{}` isn't valid, it {} \n \
+            Use set-branch-name-mode sanitize to have such names fixed",
+            name,
+            problems.join(", ")
+        ));
+    }
+
+    let sanitized = sanitize_branch_name(name);
+    let problems = branch_name_problems(&sanitized);
+    if !problems.is_empty() {
+        return Err(anyhow!(
+            "Branch name `{}` can't be made valid, it {}",
+            name,
+            problems.join(", ")
+        ));
+    }
+    Ok(sanitized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_branch_number_when_no_numbers_in_branch() {
+        let output = get_branch_number_from_branch_name("main", None);
+        assert!(output.is_err());
+    }
+
+    #[test]
+    fn get_branch_number_when_number_in_branch() {
+        let output = get_branch_number_from_branch_name("feature/1234/something-different", None);
+        assert_eq!(output.unwrap(), "1234");
+    }
+
+    #[test]
+    fn get_branch_number_takes_longest_number_instead_of_all_digits() {
+        let output = get_branch_number_from_branch_name("feature/v2-1234-fix", None);
+        assert_eq!(output.unwrap(), "1234");
+    }
+
+    #[test]
+    fn get_branch_number_from_pattern_group() {
+        let pattern = compile_branch_pattern(r"^\w+/(?P<b>\d+)-").unwrap();
+        let output = get_branch_number_from_branch_name("feature/12-fix-v1234", Some(&pattern));
+        assert_eq!(output.unwrap(), "12");
+
+        let output = get_branch_number_from_branch_name("main", Some(&pattern));
+        assert!(output.is_err());
+    }
+
+    #[test]
+    fn get_branch_captures_by_name() {
+        let pattern = compile_branch_pattern(r"(?P<kind>\w+)/(?P<ticket>[A-Z]+-\d+)").unwrap();
+        let captures = get_branch_captures("feature/PAY-4821-fix", &pattern);
+        assert_eq!(captures.get("kind").unwrap(), "feature");
+        assert_eq!(captures.get("ticket").unwrap(), "PAY-4821");
+    }
+
+    #[test]
+    fn compile_branch_pattern_requires_named_group() {
+        assert!(compile_branch_pattern(r"([A-Z]+-\d+)").is_err());
+        assert!(compile_branch_pattern(r"(?P<ticket>[A-Z+-\d+)").is_err());
+    }
+
+    #[test]
+    fn finds_what_check_ref_format_rejects() {
+        assert!(branch_name_problems("feature/PAY-12-fix.login").is_empty());
+        assert_eq!(
+            branch_name_problems("-x/.y:z~ ~/a..b.lock"),
+            vec![
+                "contains `:`, `~`, space",
+                "contains `..`",
+                "starts with `-`",
+                "has part starting with `.`",
+                "has part ending with `.lock`"
+            ]
+        );
+        assert_eq!(branch_name_problems("@"), vec!["can't be `@`"]);
+    }
+
+    #[test]
+    fn sanitizes_or_refuses_invalid_names() {
+        assert_eq!(
+            valid_branch_name("feat:-fix^login?/..x.lock.", BranchNameMode::Sanitize).unwrap(),
+            "feat-fix-login/x"
+        );
+        assert_eq!(
+            valid_branch_name("a@{1}//b\t", BranchNameMode::Sanitize).unwrap(),
+            "a-{1}/b"
+        );
+        assert_eq!(
+            valid_branch_name("fix login", BranchNameMode::Strict)
+                .unwrap_err()
+                .to_string(),
+            "Branch name `fix login` isn't valid, it contains space \n \
+            Use set-branch-name-mode sanitize to have such names fixed"
+        );
+        assert!(valid_branch_name("..", BranchNameMode::Sanitize).is_err());
+    }
+}
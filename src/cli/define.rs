@@ -2,7 +2,11 @@ use clap::{builder::styling, Arg, ArgAction, Command};
 
 pub fn build_cli_commands() -> Command {
     Command::new("")
-        .arg(Arg::new("config").required(false))
+        .arg(
+            Arg::new("config")
+                .required(false)
+                .value_parser(clap::value_parser!(std::path::PathBuf)),
+        )
         .subcommand(
             Command::new("set-branch-prefix")
                 .arg(Arg::new("key").required(false))
@@ -29,10 +33,24 @@ pub fn build_cli_commands() -> Command {
                     git-helpe-rs bf fu 123 'new cli for stuff automation' \n\
                     will checkout in the following way: \n\
                     git checkout -b feature-123/utils-new-cli-for-stuff-automation \n\
-                    you try anything funky with args to interpolate on your own \n\
+                    you try anything funky with args to interpolate on your own \n\n\
+                    Values are transformed with filters after |, for example: \n\
+                    feature/{ticket|upper}-{summary|slug|truncate:50} \n\
+                    available filters: slug, upper, lower, snake, kebab, truncate:<n> \n\
+                    values without filters have spaces replaced with - \n\n\
+                    When branch already exists, --existing overrides set-existing-branch: \n\
+                    git-helpe-rs set-branch-template review 'review/{}' --existing switch \n\
                     ",
                 )
-                .about("Set template that can be used when switching branches"),
+                .about("Set template that can be used when switching branches")
+                .add_template_metadata_args()
+                .arg(
+                    Arg::new("existing")
+                        .long("existing")
+                        .value_parser(["ask", "switch", "suffix", "fail", "config"])
+                        .help("What bt does when branch already exists \n\
+                        config goes back to the one set with set-existing-branch"),
+                ),
         )
         .subcommand(
             Command::new("bp")
@@ -41,8 +59,11 @@ pub fn build_cli_commands() -> Command {
                 .after_help(
                     "Will use contents of your clipboard to checkout you to\n\
                     branch with prefix that's under the given key. \n\
-                     Valid clipboard contents when calling this command looks like this: \n\
-                    git checkout -b name-of-your-branch \n\
+                    Clipboard can hold any of: \n\
+                    git checkout -b name-of-your-branch or git switch -c name-of-your-branch \n\
+                    name-of-your-branch \n\
+                    Jira, GitHub or GitLab issue URL, e.g. https://acme.atlassian.net/browse/PAY-12 \n\
+                    issue title, e.g. PAY-12 Fix login, which becomes PAY-12-fix-login \n\
                     If there is a valid content in your clipboard \n\
                     after running following commands: \n\n\
                     git-helpe-rs set-branch-prefix f 'feature/' \n\
@@ -56,20 +77,27 @@ pub fn build_cli_commands() -> Command {
         )
         .subcommand(
             Command::new("bt")
-                .arg(Arg::new("interpolate-values").required(true).num_args(0..))
+                .arg(
+                    Arg::new("interpolate-values")
+                        .required(false)
+                        .num_args(0..)
+                        .help("Values for {} in order, or name=value for {name} \n\
+                        write name\\=value to fill {} with name=value"),
+                )
                 .arg(Arg::new("key").short('k').required(false).help(
                     "Specify which template you want to use \n\
                     if you omit this param default template will be used",
                 ))
                 .about("Check out to a branch based on template")
                 .add_copy_flag()
-                .add_dry_run_flag(),
+                .add_dry_run_flag()
+                .add_interactive_flag(),
         )
         // ========== COMMIT-RELATED COMMANDS ========== //
         .subcommand(
             Command::new("set-commit")
                 .arg(Arg::new("template").required(true).help(
-                    "Template has places to interpolate marked with {}, [], {b} \n\n\
+                    "Template has places to interpolate marked with {}, {[]}, {b} \n\n\
                     When you provide: \n\
                     '[{}] - {}' \n\
                     as your commit template, you will be able to run \n\
@@ -77,10 +105,15 @@ pub fn build_cli_commands() -> Command {
                     git-helpe-rs commit 123 'fix gpu issues' \n\
                     and this commit message will be added: \n\
                     git commit -m \'[123] - fix gpu issues\' \n\n\
-                    If you provide [] in your template you will have to \n\
+                    You can also name places to interpolate: \n\
+                    '[{ticket}] {scope}: {summary}' \n\
+                    and pass values as name=value, each name \n\
+                    is filled everywhere it occurs: \n\
+                    git-helpe-rs c ticket=123 scope=cli summary='fix gpu issues' \n\n\
+                    If you provide {[]} in your template you will have to \n\
                     set autocomplete value with set-auto-complete \n\
-                    with the same number of args as number of [] \n\
-                    in provided template \n\n\
+                    with the same number of args as number of {[]} \n\
+                    in provided template and run c with -a flag \n\n\
                     if you provide {b} in your template you should \n\
                      - have some number in your branch \n\
                      - run git-helpers-c with -b flag \n\
@@ -101,17 +134,173 @@ pub fn build_cli_commands() -> Command {
                     You can use {{}} for places to interpolate \n\
                     and {[]} for places to autocomplete. \n\
                     and {b} as places to autocomplete from number in branch. \n\
+                    Any place can use filters after |, e.g. {summary|lower} \n\
+                    available filters: slug, upper, lower, snake, kebab, truncate:<n> \n\n\
+                    Places can be optional or have a default value: \n\
+                    {type=chore}({scope?}): {msg} \n\
+                    brackets around an empty optional place are dropped too. \n\
+                    Wrap a part in {? } to drop it when any place in it is empty: \n\
+                    {msg}{? refs {ticket}} \n\n\
+                    Built-in places filled from git and clock: \n\
+                    {date} {date:%d.%m.%Y} {user.name} {user.email} \n\
+                    {branch} {repo} {remote} {short_sha} \n\
+                    {ticket} {ticket.key} {ticket.number} - see set-ticket-keys \n\
+                    {coauthors} - people you pair with, see pair \n\n\
+                    Templates can describe themselves and their places: \n\
+                    git-helpe-rs set-commit -k fix '[{ticket}] fix: {}' \\\n\
+                        --description 'Bug fix for a ticket' --tag jira \\\n\
+                        --prompt 1='What was broken' --validate ticket='[A-Z]+-\\d+' \n\
+                    Setting template again keeps what you don't pass. \n\
+                    It's shown by commit-template list and c --help \n\n\
+                    Template is the subject, body and trailers can be added: \n\
+                    git-helpe-rs set-commit -k fix 'fix: {}' --body '{why?}' \\\n\
+                        --trailer 'Refs: {ticket}' \n\
+                    body is wrapped to width set with set-body-width (72 by default), \n\
+                    trailers that end up empty are left out \n\
                     ",
+                )
+                .add_template_metadata_args()
+                .arg(
+                    Arg::new("body")
+                        .long("body")
+                        .help("Body of commit message, places work as in template \n\
+                        pass empty string to remove it"),
+                )
+                .arg(
+                    Arg::new("trailer")
+                        .long("trailer")
+                        .value_name("KEY: VALUE")
+                        .action(ArgAction::Append)
+                        .help("Trailer of commit message, e.g. 'Refs: {ticket}' \n\
+                        pass empty value to remove it, e.g. 'Refs:'"),
+                ),
+        )
+        .subcommand(
+            Command::new("set-body-width")
+                .about("Set width to which body of commit message is wrapped")
+                .arg(
+                    Arg::new("width")
+                        .required(true)
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Width in characters, 72 by default, 0 turns wrapping off"),
                 ),
         )
         .subcommand(
             Command::new("set-auto-complete")
                 .about("set value that will be used to autocomplete commit template")
-                .arg(Arg::new("auto_complete_value").required(true).num_args(0..)),
+                .arg(Arg::new("auto_complete_value").required(true).num_args(0..))
+                .after_help(
+                    "Values are used in order for each {[]} in commit template \n\
+                    when running c with -a flag. For example: \n\
+                    git-helpe-rs set-commit '{[]}: {}' \n\
+                    git-helpe-rs set-auto-complete 'PAY' \n\
+                    git-helpe-rs c -a 'fix login' \n\
+                    will commit with message 'PAY: fix login' \n\
+                    ",
+                ),
+        )
+        .subcommand(
+            Command::new("set-branch-pattern")
+                .about("Set regex used to take values from current branch name")
+                .arg(Arg::new("pattern").required(true).help(
+                    "Regex with named groups, e.g. '(?P<ticket>[A-Z]+-\\d+)' \n\
+                    pass empty string to remove it",
+                ))
+                .after_help(
+                    "Each named group of the pattern matched against current branch \n\
+                    can be used in commit template under its name. \n\
+                    For example: \n\
+                    git-helpe-rs set-branch-pattern '(?P<ticket>[A-Z]+-\\d+)' \n\
+                    git-helpe-rs set-commit '[{ticket}] {}' \n\
+                    git-helpe-rs c 'fix login' \n\
+                    on branch feature/PAY-12-login will commit with '[PAY-12] fix login' \n\
+                    Group named b is used for {b} when running c with -b flag \n\
+                    ",
+                ),
+        )
+        .subcommand(
+            Command::new("set-branch-name-mode")
+                .about("Set what happens to branch names git would reject")
+                .arg(
+                    Arg::new("mode")
+                        .required(true)
+                        .value_parser(["sanitize", "strict"])
+                        .help("sanitize by default"),
+                )
+                .after_help(
+                    "Branch names made by bt and bp are checked against rules of \n\
+                    git check-ref-format, e.g. no ~ ^ : ? * [ \\ spaces, .., @{ \n\
+                    or parts starting with . or ending with .lock. \n\
+                    sanitize - replaces invalid characters with - and drops invalid parts \n\
+                    strict - refuses invalid names and explains why \n\
+                    ",
+                ),
+        )
+        .subcommand(
+            Command::new("set-existing-branch")
+                .about("Set what bt and bp do when branch already exists")
+                .arg(
+                    Arg::new("action")
+                        .required(true)
+                        .value_parser(["ask", "switch", "suffix", "fail"])
+                        .help("ask by default"),
+                )
+                .after_help(
+                    "Before checking out, local branches and remote-tracking branches \n\
+                    like origin/<name> are looked up. When one exists: \n\
+                    ask - offers to switch to it, create it tracking the remote one \n\
+                    or create a branch with free suffix, fails outside of terminal \n\
+                    switch - switches to local branch, or creates one tracking remote branch \n\
+                    suffix - creates <name>-2, <name>-3, ... whichever is free first \n\
+                    fail - stops without checking out \n\
+                    Branch templates can override it with --existing of set-branch-template \n\
+                    ",
+                ),
+        )
+        .subcommand(
+            Command::new("set-ticket-keys")
+                .about("Set project keys or patterns used to find ticket for {ticket}")
+                .arg(Arg::new("ticket_keys").required(false).num_args(0..).help(
+                    "Project keys like PAY OPS or regexes matching whole ticket \n\
+                    pass nothing to go back to default pattern",
+                ))
+                .after_help(
+                    "Ticket is taken from current branch name, and if there is none \n\
+                    from your clipboard when set-ticket-from-clipboard is on. \n\
+                    It can be used in templates as: \n\
+                    {ticket} - PAY-4821 \n\
+                    {ticket.key} - PAY \n\
+                    {ticket.number} - 4821 \n\
+                    Without ticket keys anything like ABC-123 is taken as a ticket. \n\
+                    Regexes can name key and number groups: (?P<key>gh)(?P<number>\\d+) \n\
+                    ",
+                ),
+        )
+        .subcommand(
+            Command::new("set-ticket-from-clipboard")
+                .about("Set whether ticket is looked for in clipboard when branch has none")
+                .arg(
+                    Arg::new("value")
+                        .required(true)
+                        .value_parser(["on", "off"])
+                        .help("off by default"),
+                )
+                .after_help(
+                    "When on, paste command is run to find ticket for {ticket} \n\
+                    whenever current branch name has none. \n\
+                    prepare-commit-msg hook never reads clipboard \n\
+                    ",
+                ),
         )
         .subcommand(
             Command::new("c")
-                .arg(Arg::new("interpolate-values").required(true).num_args(0..))
+                .arg(
+                    Arg::new("interpolate-values")
+                        .required(false)
+                        .num_args(0..)
+                        .help("Values for {} in order, or name=value for {name} \n\
+                        write name\\=value to fill {} with name=value"),
+                )
                 .arg(
                     Arg::new("auto-complete")
                         .short('a')
@@ -129,18 +318,295 @@ pub fn build_cli_commands() -> Command {
                         .short('b')
                         .action(ArgAction::SetTrue)
                         .help(
-                            "Will try to catch any number from branch name \n\
+                            "Will take number from branch name \n\
                     and use it as a value for {b} in your template \n\
-                    if your template has no {b} it will panic \n\
+                    it's the longest number in branch name or group b \n\
+                    of pattern set with set-branch-pattern \n\
+                    if your template has no {b} it will fail \n\
+                    for tickets like PAY-4821 use {ticket} in template instead \n\
                     ",
                         ),
                 )
+                .arg(
+                    Arg::new("edit")
+                        .short('e')
+                        .long("edit")
+                        .action(ArgAction::SetTrue)
+                        .help(
+                            "Open rendered message in your editor before committing \n\
+                    editor is taken from GIT_EDITOR, core.editor, VISUAL or EDITOR \n\
+                    lines starting with # are dropped, empty message aborts the commit",
+                        ),
+                )
+                .arg(
+                    Arg::new("trailer")
+                        .long("trailer")
+                        .value_name("KEY: VALUE")
+                        .action(ArgAction::Append)
+                        .help(
+                            "Trailer added after those from template, e.g. \n\
+                    --trailer 'Co-authored-by: Ann <ann@example.com>'",
+                        ),
+                )
+                .arg(
+                    Arg::new("solo")
+                        .long("solo")
+                        .action(ArgAction::SetTrue)
+                        .help("Don't add Co-authored-by of people you pair with"),
+                )
                 .about("Commit using one of templates")
                 .add_copy_flag()
+                .add_dry_run_flag()
+                .add_interactive_flag(),
+        )
+        .subcommand(
+            Command::new("cc")
+                .about("Commit with Conventional Commits message")
+                .arg(
+                    Arg::new("type")
+                        .required(true)
+                        .help("Type of change, one of set with set-conventional-types"),
+                )
+                .arg(
+                    Arg::new("description")
+                        .required(true)
+                        .num_args(1..)
+                        .help("Short summary of the change, words are joined with spaces"),
+                )
+                .arg(
+                    Arg::new("scope")
+                        .short('s')
+                        .long("scope")
+                        .help("Part of codebase the change is in, e.g. api"),
+                )
+                .arg(
+                    Arg::new("breaking")
+                        .long("breaking")
+                        .action(ArgAction::SetTrue)
+                        .help("Mark change as breaking with ! after type and scope"),
+                )
+                .arg(
+                    Arg::new("breaking-change")
+                        .long("breaking-change")
+                        .value_name("DESCRIPTION")
+                        .help(
+                            "Add BREAKING CHANGE: footer with what breaks \n\
+                    implies --breaking",
+                        ),
+                )
+                .arg(
+                    Arg::new("body")
+                        .long("body")
+                        .help("Body of commit message, wrapped like body of templates"),
+                )
+                .arg(
+                    Arg::new("edit")
+                        .short('e')
+                        .long("edit")
+                        .action(ArgAction::SetTrue)
+                        .help("Open message in your editor before committing"),
+                )
+                .arg(
+                    Arg::new("trailer")
+                        .long("trailer")
+                        .value_name("KEY: VALUE")
+                        .action(ArgAction::Append)
+                        .help("Trailer added after BREAKING CHANGE footer"),
+                )
+                .arg(
+                    Arg::new("solo")
+                        .long("solo")
+                        .action(ArgAction::SetTrue)
+                        .help("Don't add Co-authored-by of people you pair with"),
+                )
+                .after_help(
+                    "Message is checked against Conventional Commits before git commit \n\
+                    is run, also after editing it. For example: \n\
+                    git-helpe-rs cc feat -s api --breaking-change 'tokens are required' require tokens \n\
+                    will commit with message \n\
+                    feat(api)!: require tokens \n\
+                    \n\
+                    BREAKING CHANGE: tokens are required \n\
+                    ",
+                )
+                .add_copy_flag()
                 .add_dry_run_flag(),
         )
+        .subcommand(
+            Command::new("set-conventional-types")
+                .about("Set types allowed in messages of cc")
+                .arg(Arg::new("types").required(false).num_args(0..).help(
+                    "Types like feat fix docs \n\
+                    pass nothing to go back to default types",
+                )),
+        )
+        .subcommand(
+            Command::new("set-subject-max-length")
+                .about("Set how long first line of cc message can be")
+                .arg(
+                    Arg::new("length")
+                        .required(true)
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Length in characters, 72 by default, 0 turns the check off"),
+                ),
+        )
+        // ============= SECTIONS ============= //
+        .subcommand(section_command(
+            "commit-template",
+            "List, get, delete, rename or copy commit templates",
+            "key",
+        ))
+        .subcommand(section_command(
+            "branch-template",
+            "List, get, delete, rename or copy branch templates",
+            "key",
+        ))
+        .subcommand(section_command(
+            "branch-prefix",
+            "List, get, delete, rename or copy branch prefixes",
+            "key",
+        ))
+        .subcommand(
+            section_command(
+                "auto-complete",
+                "List, get, delete, move or copy autocomplete values",
+                "position",
+            )
+            .after_help(
+                "Autocomplete values are used in order for {[]}, \n\
+                so they are addressed by position starting from 1. \n\
+                rename moves value to given position, copy inserts it there",
+            ),
+        )
+        // ============= PAIRING ============= //
+        .subcommand(
+            Command::new("pair")
+                .about("Manage people you pair with, added as co-authors of commits")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("list").about("Show roster, people paired with now marked with *"),
+                )
+                .subcommand(
+                    Command::new("add")
+                        .about("Add person to roster")
+                        .arg(Arg::new("handle").required(true).help("Short name, e.g. ann"))
+                        .arg(
+                            Arg::new("coauthor")
+                                .required(true)
+                                .help("Name and email, e.g. 'Ann Lee <ann@example.com>'"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Remove person from roster")
+                        .arg(Arg::new("handle").required(true)),
+                )
+                .subcommand(
+                    Command::new("start")
+                        .about("Start pairing with given people")
+                        .arg(Arg::new("handles").required(true).num_args(1..)),
+                )
+                .subcommand(
+                    Command::new("end")
+                        .about("Stop pairing with given people, or with everyone")
+                        .arg(Arg::new("handles").required(false).num_args(0..)),
+                )
+                .after_help(
+                    "While pairing each commit made with c gets a trailer \n\
+                    Co-authored-by: Name <email> \n\
+                    for every person paired with, unless c is run with --solo. \n\
+                    Templates can use {coauthors} to list them. For example: \n\
+                    git-helpe-rs pair add ann 'Ann Lee <ann@example.com>' \n\
+                    git-helpe-rs pair start ann \n\
+                    git-helpe-rs c 'fix login' \n\
+                    git-helpe-rs pair end \n\
+                    ",
+                ),
+        )
+        // =============== HOOKS ============== //
+        .subcommand(
+            Command::new("hook")
+                .about("Entry points run by git hooks installed with install-hooks")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("commit-msg")
+                        .about("Check commit message against commit templates")
+                        .arg(
+                            Arg::new("file")
+                                .required(true)
+                                .help("File with commit message, passed by git"),
+                        )
+                        .arg(
+                            Arg::new("conventional")
+                                .long("conventional")
+                                .action(ArgAction::SetTrue)
+                                .help("Check against Conventional Commits instead of templates"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("prepare-commit-msg")
+                        .about("Pre-fill message of plain git commit with default template")
+                        .arg(
+                            Arg::new("file")
+                                .required(true)
+                                .help("File with commit message, passed by git"),
+                        )
+                        .arg(Arg::new("source").required(false).help(
+                            "Where message comes from: message, template, merge, squash or commit \n\
+                            message isn't pre-filled when it's given",
+                        ))
+                        .arg(
+                            Arg::new("sha")
+                                .required(false)
+                                .help("Commit given with -c, -C or --amend"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("install-hooks")
+                .about("Install git hooks into core.hooksPath or .git/hooks")
+                .arg(
+                    Arg::new("conventional")
+                        .long("conventional")
+                        .action(ArgAction::SetTrue)
+                        .help("Check messages against Conventional Commits instead of templates"),
+                )
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .action(ArgAction::SetTrue)
+                        .help("Overwrite hooks that weren't installed by git-helpe-rs"),
+                )
+                .after_help(
+                    "commit-msg hook rejects commits whose subject doesn't match \n\
+                    any commit template, also those made with plain git commit or an IDE. \n\
+                    Merge, revert, fixup! and squash! messages written by git are let through, \n\
+                    git commit --no-verify skips the check. \n\
+                    prepare-commit-msg hook opens editor of plain git commit with default \n\
+                    template filled with values from branch, like c -b does, \n\
+                    messages given with -m, merges, squashes and amends are left as they are. \n\
+                    ",
+                ),
+        )
         // ============== OTHERS ============== //
-        .subcommand(Command::new("show").about("Show current config in plain JSON"))
+        .subcommand(
+            Command::new("show")
+                .about("Show current config in plain JSON")
+                .arg(
+                    Arg::new("origin")
+                        .short('o')
+                        .long("origin")
+                        .action(ArgAction::SetTrue)
+                        .help("Show which layer each value comes from: default, global or repo"),
+                )
+                .after_help(
+                    "Config is merged key by key from built-in defaults, \n\
+                    global config file and .git-helpe-rs.toml/.yaml/.json committed in repository root \n\
+                    (or placed in .git directory). Repo config wins, \n\
+                    except for clipboard commands, which are taken only from global config. \n\
+                    All set-* commands write only to global config",
+                ),
+        )
         .subcommand(
             Command::new("set-clipboard-command")
                 .about("[WIP] Set pair of copy&paste command which will be used")
@@ -151,6 +617,71 @@ pub fn build_cli_commands() -> Command {
                 ",
                 )),
         )
+        .subcommand(
+            Command::new("config")
+                .about("Manage config files")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("convert")
+                        .about("Convert config to another format")
+                        .arg(
+                            Arg::new("format")
+                                .required(true)
+                                .value_parser(["toml", "yaml", "json"])
+                                .help("Format of new config file"),
+                        )
+                        .arg(
+                            Arg::new("repo")
+                                .long("repo")
+                                .action(ArgAction::SetTrue)
+                                .help("Convert config of current repository instead of global one"),
+                        )
+                        .after_help(
+                            "Config format is picked by file extension: \n\
+                            .toml, .yaml/.yml or .json, file without extension is JSON \n\
+                            New file is written next to the old one, e.g. .git-helpe-rs-config.toml \n\
+                            and the old one is kept as <old name>.<date>.bak",
+                        ),
+                )
+                .subcommand(
+                    Command::new("repair")
+                        .about("Keep valid sections of config that can't be parsed")
+                        .arg(
+                            Arg::new("repo")
+                                .long("repo")
+                                .action(ArgAction::SetTrue)
+                                .help("Repair config of current repository instead of global one"),
+                        )
+                        .after_help(
+                            "Config that can't be parsed is never written over, \n\
+                            every command fails and points to the line with error. \n\
+                            Repair reads every section, or every entry of broken section, \n\
+                            on its own and writes back those that are valid. \n\
+                            Original file is kept as <name>.<date>.bak",
+                        ),
+                )
+                .subcommand(
+                    Command::new("migrate")
+                        .about("Rewrite config in the newest schema version")
+                        .arg(
+                            Arg::new("dry-run")
+                                .long("dry-run")
+                                .action(ArgAction::SetTrue)
+                                .help("Only print the diff, don't write anything"),
+                        )
+                        .arg(
+                            Arg::new("repo")
+                                .long("repo")
+                                .action(ArgAction::SetTrue)
+                                .help("Migrate config of current repository instead of global one"),
+                        )
+                        .after_help(
+                            "Config keeps its layout version in schema_version, \n\
+                            file without it is version 1. Older configs are upgraded \n\
+                            in memory on every run, migrate writes the upgrade to the file",
+                        ),
+                ),
+        )
         .subcommand(
             Command::new("generate-autocompletion-script")
                 .about("Generates bash completion script")
@@ -165,6 +696,40 @@ pub fn build_cli_commands() -> Command {
         .get_styles()
 }
 
+/// Same subcommands for every map in config
+fn section_command(name: &'static str, about: &'static str, key_name: &'static str) -> Command {
+    Command::new(name)
+        .about(about)
+        .subcommand_required(true)
+        .subcommand(Command::new("list").about("Show all entries"))
+        .subcommand(
+            Command::new("get")
+                .about("Show single entry")
+                .arg(Arg::new("key").value_name(key_name).required(true)),
+        )
+        .subcommand(
+            Command::new("delete")
+                .about("Remove entry from global config")
+                .arg(Arg::new("key").value_name(key_name).required(true)),
+        )
+        .subcommand(
+            Command::new("rename")
+                .about("Change key of entry, keeping its place")
+                .arg(Arg::new("from").value_name(key_name).required(true))
+                .arg(Arg::new("to").required(true)),
+        )
+        .subcommand(
+            Command::new("copy")
+                .about("Copy entry under new key")
+                .arg(Arg::new("from").value_name(key_name).required(true))
+                .arg(Arg::new("to").required(true)),
+        )
+        .after_help(
+            "list and get show merged global and repo config, \n\
+            changes are written to global config only",
+        )
+}
+
 trait AddCopyFlag {
     fn add_copy_flag(self) -> Self;
 }
@@ -198,6 +763,67 @@ impl AddDryRunFlag for Command {
     }
 }
 
+trait AddInteractiveFlag {
+    fn add_interactive_flag(self) -> Self;
+}
+
+impl AddInteractiveFlag for Command {
+    fn add_interactive_flag(self) -> Self {
+        self.arg(
+            Arg::new("interactive")
+                .short('i')
+                .long("interactive")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Pick template from menu when -k isn't given, ask for each \n\
+                    missing value and confirm before running git. \n\
+                    Missing values are asked for also without -i when run in terminal",
+                ),
+        )
+    }
+}
+
+trait AddTemplateMetadataArgs {
+    fn add_template_metadata_args(self) -> Self;
+}
+
+impl AddTemplateMetadataArgs for Command {
+    fn add_template_metadata_args(self) -> Self {
+        let arg_metadata = |id: &'static str, help: &'static str| {
+            Arg::new(id)
+                .long(id)
+                .value_name("NAME=VALUE")
+                .action(ArgAction::Append)
+                .help(help)
+        };
+
+        self.arg(
+            Arg::new("description")
+                .long("description")
+                .help("What the template is for, shown in list and --help"),
+        )
+        .arg(
+            Arg::new("tag")
+                .long("tag")
+                .action(ArgAction::Append)
+                .help("Tag of the template, can be given many times"),
+        )
+        .arg(arg_metadata(
+            "prompt",
+            "What value of place means, e.g. summary='What changed' \n\
+            use 1, 2, ... as name of {} places",
+        ))
+        .arg(arg_metadata(
+            "example",
+            "Example value of place, e.g. ticket=PAY-12",
+        ))
+        .arg(arg_metadata(
+            "validate",
+            "Regex whole value of place has to match, e.g. ticket='[A-Z]+-\\d+'",
+        ))
+    }
+}
+
 trait Styles {
     fn get_styles(self) -> Self;
 }
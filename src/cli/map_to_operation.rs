@@ -3,11 +3,16 @@ use std::path::PathBuf;
 use anyhow::Ok;
 use clap::ArgMatches;
 
-use crate::file_utils::config_file::get_path_to_config;
+use crate::{
+    branch_name::BranchNameMode, commit_message::parse_trailer,
+    existing_branch::ExistingBranchAction, file_utils::config_file::get_path_to_config,
+};
 
 use super::{
-    CheckoutToPrefix, CommitOperationArguments, CommitSubcommandFlags, DryRunAndCopyFlag,
-    OperationWithArguments, ParsedArguments, SetFormat, UseTemplate,
+    CheckoutToPrefix, CommitOperationArguments, CommitSubcommandFlags, ConfigSection,
+    ConventionalCommitArguments, ConvertConfig, DryRunAndCopyFlag, HookCommand, InstallHooks,
+    MigrateConfig, OperationWithArguments, PairCommand, ParsedArguments, RepairConfig,
+    SectionCommand, SectionOperation, SetFormat, ShowConfig, TemplateMetadata, UseTemplate,
 };
 
 impl TryFrom<ArgMatches> for ParsedArguments {
@@ -20,12 +25,14 @@ impl TryFrom<ArgMatches> for ParsedArguments {
                 Ok(OperationWithArguments::SetBranchPrefix(format_vals))
             }
             Some(("set-branch-template", args)) => {
-                let format_vals = get_key_val_from_arg_matches(args, "template").unwrap();
+                let mut format_vals = get_key_val_from_arg_matches(args, "template").unwrap();
+                format_vals.metadata = get_template_metadata_from_arg_matches(args)?;
 
                 Ok(OperationWithArguments::SetBranchFormat(format_vals))
             }
             Some(("set-commit", args)) => {
-                let format_vals = get_key_val_from_arg_matches(args, "template").unwrap();
+                let mut format_vals = get_key_val_from_arg_matches(args, "template").unwrap();
+                format_vals.metadata = get_template_metadata_from_arg_matches(args)?;
 
                 Ok(OperationWithArguments::SetCommitFormat(format_vals))
             }
@@ -42,17 +49,48 @@ impl TryFrom<ArgMatches> for ParsedArguments {
                     use_branch_number: should_use_number_in_branch.to_owned(),
                     dry_run: dry_run_and_copy_flags.dry_run,
                     copy: dry_run_and_copy_flags.copy,
+                    edit: args.get_flag("edit"),
+                    solo: args.get_flag("solo"),
                 };
 
                 let args = CommitOperationArguments {
                     flags: commit_flags,
-                    use_template: use_template,
+                    use_template,
+                    trailers: get_trailers_from_arg_matches(args)?,
                 };
                 let commit_operation_with_arguments = OperationWithArguments::Commit(args);
 
                 Ok(commit_operation_with_arguments)
             }
 
+            Some(("cc", args)) => {
+                let dry_run_and_copy_flags = get_dry_run_and_copy_flags(args);
+                let description: Vec<String> = args
+                    .get_many::<String>("description")
+                    .unwrap()
+                    .cloned()
+                    .collect();
+
+                Ok(OperationWithArguments::ConventionalCommit(
+                    ConventionalCommitArguments {
+                        commit_type: args.get_one::<String>("type").unwrap().to_owned(),
+                        scope: args.get_one::<String>("scope").cloned(),
+                        breaking: args.get_flag("breaking"),
+                        breaking_change: args.get_one::<String>("breaking-change").cloned(),
+                        description: description.join(" "),
+                        body: args.get_one::<String>("body").cloned(),
+                        flags: CommitSubcommandFlags {
+                            use_branch_number: false,
+                            dry_run: dry_run_and_copy_flags.dry_run,
+                            copy: dry_run_and_copy_flags.copy,
+                            edit: args.get_flag("edit"),
+                            solo: args.get_flag("solo"),
+                        },
+                        trailers: get_trailers_from_arg_matches(args)?,
+                    },
+                ))
+            }
+
             Some(("bt", args)) => {
                 let use_template = get_use_template_from_arg_matches(args);
 
@@ -76,7 +114,7 @@ impl TryFrom<ArgMatches> for ParsedArguments {
                 let clipboard_command: Vec<String> =
                     args.remove_many("copy-paste-pair").unwrap().collect();
 
-                let copy = clipboard_command.get(0).unwrap();
+                let copy = clipboard_command.first().unwrap();
                 let paste = clipboard_command.get(1).unwrap();
 
                 Ok(OperationWithArguments::SetClipboardCommands(
@@ -86,6 +124,73 @@ impl TryFrom<ArgMatches> for ParsedArguments {
                     },
                 ))
             }
+            Some(("set-auto-complete", args)) => {
+                let mut args = args.clone();
+                let auto_complete_values: Vec<String> = args
+                    .remove_many("auto_complete_value")
+                    .unwrap_or_default()
+                    .collect();
+
+                Ok(OperationWithArguments::SetAutoComplete(
+                    auto_complete_values,
+                ))
+            }
+            Some(("set-branch-pattern", args)) => {
+                let pattern = args.get_one::<String>("pattern").unwrap();
+
+                Ok(OperationWithArguments::SetBranchPattern(pattern.to_owned()))
+            }
+            Some(("set-branch-name-mode", args)) => {
+                let mode = args.get_one::<String>("mode").unwrap();
+
+                Ok(OperationWithArguments::SetBranchNameMode(
+                    BranchNameMode::try_from(mode.as_str())?,
+                ))
+            }
+            Some(("set-existing-branch", args)) => {
+                let action = args.get_one::<String>("action").unwrap();
+
+                Ok(OperationWithArguments::SetExistingBranch(
+                    ExistingBranchAction::try_from(action.as_str())?,
+                ))
+            }
+            Some(("set-body-width", args)) => {
+                let width = args.get_one::<usize>("width").unwrap();
+
+                Ok(OperationWithArguments::SetBodyWidth(width.to_owned()))
+            }
+            Some(("set-conventional-types", args)) => {
+                let types: Vec<String> = args
+                    .get_many::<String>("types")
+                    .unwrap_or_default()
+                    .cloned()
+                    .collect();
+
+                Ok(OperationWithArguments::SetConventionalTypes(types))
+            }
+            Some(("set-subject-max-length", args)) => {
+                let length = args.get_one::<usize>("length").unwrap();
+
+                Ok(OperationWithArguments::SetSubjectMaxLength(
+                    length.to_owned(),
+                ))
+            }
+            Some(("set-ticket-keys", args)) => {
+                let mut args = args.clone();
+                let ticket_keys: Vec<String> = args
+                    .remove_many("ticket_keys")
+                    .unwrap_or_default()
+                    .collect();
+
+                Ok(OperationWithArguments::SetTicketKeys(ticket_keys))
+            }
+            Some(("set-ticket-from-clipboard", args)) => {
+                let value = args.get_one::<String>("value").unwrap();
+
+                Ok(OperationWithArguments::SetTicketFromClipboard(
+                    value == "on",
+                ))
+            }
             Some(("generate-autocompletion-script", args)) => {
                 let path: PathBuf = args.get_one::<String>("output-directory").unwrap().into();
 
@@ -93,7 +198,108 @@ impl TryFrom<ArgMatches> for ParsedArguments {
                     path.to_owned(),
                 ))
             }
-            Some(("show", _args)) => Ok(OperationWithArguments::Show),
+            Some(("show", args)) => Ok(OperationWithArguments::Show(ShowConfig {
+                origin: args.get_flag("origin"),
+            })),
+            Some((
+                name @ ("commit-template" | "branch-template" | "branch-prefix" | "auto-complete"),
+                args,
+            )) => {
+                let section = match name {
+                    "commit-template" => ConfigSection::CommitTemplates,
+                    "branch-template" => ConfigSection::BranchTemplates,
+                    "branch-prefix" => ConfigSection::BranchPrefixes,
+                    _ => ConfigSection::AutocompleteValues,
+                };
+                let key = |args: &ArgMatches| args.get_one::<String>("key").unwrap().to_owned();
+                let from_to = |args: &ArgMatches| {
+                    (
+                        args.get_one::<String>("from").unwrap().to_owned(),
+                        args.get_one::<String>("to").unwrap().to_owned(),
+                    )
+                };
+                let operation = match args.subcommand() {
+                    Some(("list", _)) => SectionOperation::List,
+                    Some(("get", args)) => SectionOperation::Get(key(args)),
+                    Some(("delete", args)) => SectionOperation::Delete(key(args)),
+                    Some(("rename", args)) => {
+                        let (from, to) = from_to(args);
+                        SectionOperation::Rename { from, to }
+                    }
+                    Some(("copy", args)) => {
+                        let (from, to) = from_to(args);
+                        SectionOperation::Copy { from, to }
+                    }
+                    _ => return Err(anyhow::anyhow!("Unknown command")),
+                };
+
+                Ok(OperationWithArguments::Section(SectionCommand {
+                    section,
+                    operation,
+                }))
+            }
+            Some(("config", args)) => match args.subcommand() {
+                Some(("convert", args)) => {
+                    Ok(OperationWithArguments::ConvertConfig(ConvertConfig {
+                        format: args.get_one::<String>("format").unwrap().to_owned(),
+                        repo: args.get_flag("repo"),
+                    }))
+                }
+                Some(("repair", args)) => Ok(OperationWithArguments::RepairConfig(RepairConfig {
+                    repo: args.get_flag("repo"),
+                })),
+                Some(("migrate", args)) => {
+                    Ok(OperationWithArguments::MigrateConfig(MigrateConfig {
+                        dry_run: args.get_flag("dry-run"),
+                        repo: args.get_flag("repo"),
+                    }))
+                }
+                _ => Err(anyhow::anyhow!("Unknown command")),
+            },
+            Some(("hook", args)) => match args.subcommand() {
+                Some(("commit-msg", args)) => {
+                    Ok(OperationWithArguments::Hook(HookCommand::CommitMsg {
+                        file: args.get_one::<String>("file").unwrap().into(),
+                        conventional: args.get_flag("conventional"),
+                    }))
+                }
+                Some(("prepare-commit-msg", args)) => Ok(OperationWithArguments::Hook(
+                    HookCommand::PrepareCommitMsg {
+                        file: args.get_one::<String>("file").unwrap().into(),
+                        source: args.get_one::<String>("source").cloned(),
+                    },
+                )),
+                _ => Err(anyhow::anyhow!("Unknown command")),
+            },
+            Some(("install-hooks", args)) => {
+                Ok(OperationWithArguments::InstallHooks(InstallHooks {
+                    conventional: args.get_flag("conventional"),
+                    force: args.get_flag("force"),
+                }))
+            }
+            Some(("pair", args)) => {
+                let handles = |args: &ArgMatches| -> Vec<String> {
+                    args.get_many::<String>("handles")
+                        .unwrap_or_default()
+                        .cloned()
+                        .collect()
+                };
+                let command = match args.subcommand() {
+                    Some(("list", _)) => PairCommand::List,
+                    Some(("add", args)) => PairCommand::Add {
+                        handle: args.get_one::<String>("handle").unwrap().to_owned(),
+                        coauthor: args.get_one::<String>("coauthor").unwrap().to_owned(),
+                    },
+                    Some(("remove", args)) => {
+                        PairCommand::Remove(args.get_one::<String>("handle").unwrap().to_owned())
+                    }
+                    Some(("start", args)) => PairCommand::Start(handles(args)),
+                    Some(("end", args)) => PairCommand::End(handles(args)),
+                    _ => return Err(anyhow::anyhow!("Unknown command")),
+                };
+
+                Ok(OperationWithArguments::Pair(command))
+            }
             _ => Err(anyhow::anyhow!("Unknown command")),
         };
 
@@ -118,9 +324,60 @@ fn get_key_val_from_arg_matches(
     Ok(SetFormat {
         key: key.to_owned(),
         value: value.to_owned(),
+        metadata: TemplateMetadata::default(),
     })
 }
 
+fn get_template_metadata_from_arg_matches(
+    args: &ArgMatches,
+) -> Result<TemplateMetadata, anyhow::Error> {
+    let named_values = |id: &str| -> Result<Vec<(String, String)>, anyhow::Error> {
+        args.get_many::<String>(id)
+            .unwrap_or_default()
+            .map(|value| {
+                value
+                    .split_once('=')
+                    .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Expected NAME=VALUE for --{}, got {}", id, value)
+                    })
+            })
+            .collect()
+    };
+
+    Ok(TemplateMetadata {
+        description: args.get_one::<String>("description").cloned(),
+        tags: args
+            .get_many::<String>("tag")
+            .map(|tags| tags.cloned().collect()),
+        prompts: named_values("prompt")?,
+        examples: named_values("example")?,
+        validations: named_values("validate")?,
+        body: args.try_get_one::<String>("body").ok().flatten().cloned(),
+        trailers: args
+            .try_get_many::<String>("trailer")
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+            .map(|trailer| parse_trailer(trailer))
+            .collect::<Result<Vec<(String, String)>, anyhow::Error>>()?,
+        existing_branch: match args.try_get_one::<String>("existing").ok().flatten() {
+            Some(action) if action == "config" => Some(None),
+            Some(action) => Some(Some(ExistingBranchAction::try_from(action.as_str())?)),
+            None => None,
+        },
+    })
+}
+
+fn get_trailers_from_arg_matches(
+    args: &ArgMatches,
+) -> Result<Vec<(String, String)>, anyhow::Error> {
+    args.get_many::<String>("trailer")
+        .unwrap_or_default()
+        .map(|trailer| parse_trailer(trailer))
+        .collect()
+}
+
 fn get_dry_run_and_copy_flags(args: &ArgMatches) -> DryRunAndCopyFlag {
     let copy = args
         .get_one::<bool>("copy-flag")
@@ -137,10 +394,15 @@ fn get_use_template_from_arg_matches(args: &ArgMatches) -> UseTemplate {
     } else {
         "default".to_owned()
     };
+    let interactive = args.get_flag("interactive");
+    let pick_key = interactive && args.get_one::<String>("key").is_none();
 
     let mut args = args.clone();
 
-    let interpolate_values: Vec<String> = args.remove_many("interpolate-values").unwrap().collect();
+    let interpolate_values: Vec<String> = args
+        .remove_many("interpolate-values")
+        .map(|values| values.collect())
+        .unwrap_or_default();
 
     let use_autocomplete = match args.try_contains_id("auto-complete") {
         Err(_) => &false,
@@ -151,10 +413,12 @@ fn get_use_template_from_arg_matches(args: &ArgMatches) -> UseTemplate {
     let dry_run_and_copy_flags = get_dry_run_and_copy_flags(&args);
 
     UseTemplate {
-        interpolate_values: interpolate_values,
-        key: key,
+        interpolate_values,
+        key,
         use_autocomplete: use_autocomplete.to_owned(),
         dry_run: dry_run_and_copy_flags.dry_run,
         copy: dry_run_and_copy_flags.copy,
+        interactive,
+        pick_key,
     }
 }
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use crate::{branch_name::BranchNameMode, existing_branch::ExistingBranchAction};
+
 pub mod define;
 
 pub mod map_to_operation;
@@ -17,6 +19,25 @@ pub enum Operation {
 pub struct SetFormat {
     pub key: String,
     pub value: String,
+    pub metadata: TemplateMetadata,
+}
+
+/// Given to set-commit and set-branch-template, anything that isn't given
+/// is kept from template already stored under the key
+#[derive(Default)]
+pub struct TemplateMetadata {
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    /// Pairs of place name and value, for `{}` places name is position
+    pub prompts: Vec<(String, String)>,
+    pub examples: Vec<(String, String)>,
+    pub validations: Vec<(String, String)>,
+    /// Empty body removes it
+    pub body: Option<String>,
+    /// Trailer with empty value is removed
+    pub trailers: Vec<(String, String)>,
+    /// Some(None) makes branch template use existing_branch of config again
+    pub existing_branch: Option<Option<ExistingBranchAction>>,
 }
 
 pub struct CheckoutToPrefix {
@@ -36,32 +57,143 @@ pub struct UseTemplate {
     pub use_autocomplete: bool,
     pub copy: bool,
     pub dry_run: bool,
+    /// Ask for missing values and confirm before running git
+    pub interactive: bool,
+    /// Interactive and no key given, template is picked from menu
+    pub pick_key: bool,
 }
 
+#[derive(Default)]
 pub struct CommitSubcommandFlags {
     pub use_branch_number: bool,
     pub copy: bool,
     pub dry_run: bool,
+    /// Open message in editor before committing
+    pub edit: bool,
+    /// Skip Co-authored-by trailers of people paired with
+    pub solo: bool,
 }
 
 pub struct CommitOperationArguments {
     pub use_template: UseTemplate,
     pub flags: CommitSubcommandFlags,
+    /// Added after trailers of template
+    pub trailers: Vec<(String, String)>,
+}
+
+/// Parts of `type(scope)!: description` message given with cc
+pub struct ConventionalCommitArguments {
+    pub commit_type: String,
+    pub scope: Option<String>,
+    /// Adds `!` after type and scope
+    pub breaking: bool,
+    /// Description in `BREAKING CHANGE:` footer, adds `!` too
+    pub breaking_change: Option<String>,
+    pub description: String,
+    pub body: Option<String>,
+    pub flags: CommitSubcommandFlags,
+    pub trailers: Vec<(String, String)>,
+}
+
+pub struct ShowConfig {
+    pub origin: bool,
+}
+
+pub struct ConvertConfig {
+    pub format: String,
+    pub repo: bool,
+}
+
+pub struct MigrateConfig {
+    pub dry_run: bool,
+    pub repo: bool,
+}
+
+pub struct RepairConfig {
+    pub repo: bool,
 }
+
 pub struct SetClipboardCommands {
     pub copy: String,
     pub paste: String,
 }
 
+pub enum ConfigSection {
+    CommitTemplates,
+    BranchTemplates,
+    BranchPrefixes,
+    AutocompleteValues,
+}
+
+pub enum SectionOperation {
+    List,
+    Get(String),
+    Delete(String),
+    Rename { from: String, to: String },
+    Copy { from: String, to: String },
+}
+
+pub enum PairCommand {
+    List,
+    Add {
+        handle: String,
+        coauthor: String,
+    },
+    Remove(String),
+    /// Handles to pair with, replacing those paired with now
+    Start(Vec<String>),
+    /// Handles to stop pairing with, all when empty
+    End(Vec<String>),
+}
+
+pub enum HookCommand {
+    /// Message file git passes to commit-msg hook
+    CommitMsg { file: PathBuf, conventional: bool },
+    /// Source of message git passes to prepare-commit-msg, none for plain git commit
+    PrepareCommitMsg {
+        file: PathBuf,
+        source: Option<String>,
+    },
+}
+
+pub struct InstallHooks {
+    /// commit-msg checks messages against Conventional Commits instead of templates
+    pub conventional: bool,
+    /// Overwrite hooks not installed by git-helpe-rs
+    pub force: bool,
+}
+
+pub struct SectionCommand {
+    pub section: ConfigSection,
+    pub operation: SectionOperation,
+}
+
 pub enum OperationWithArguments {
     Commit(CommitOperationArguments),
+    ConventionalCommit(ConventionalCommitArguments),
     BranchFromClipboard(CheckoutToPrefix),
     BranchFromTemplate(UseTemplate),
     SetCommitFormat(SetFormat),
     SetBranchFormat(SetFormat),
     SetBranchPrefix(SetFormat),
     SetClipboardCommands(SetClipboardCommands),
-    Show,
+    SetAutoComplete(Vec<String>),
+    SetBranchPattern(String),
+    SetBranchNameMode(BranchNameMode),
+    SetExistingBranch(ExistingBranchAction),
+    SetBodyWidth(usize),
+    SetConventionalTypes(Vec<String>),
+    SetSubjectMaxLength(usize),
+    Pair(PairCommand),
+    Hook(HookCommand),
+    InstallHooks(InstallHooks),
+    SetTicketKeys(Vec<String>),
+    SetTicketFromClipboard(bool),
+    Show(ShowConfig),
+    Section(SectionCommand),
+    ConvertConfig(ConvertConfig),
+    RepairConfig(RepairConfig),
+    MigrateConfig(MigrateConfig),
     GenerateAutocompletionScript(PathBuf),
 }
 
@@ -0,0 +1,246 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::{
+    template::Filter,
+    ticket::{find_ticket, Ticket},
+};
+
+/// Branch name read from clipboard, before prefix is added
+#[derive(Debug, PartialEq)]
+pub struct Recognized {
+    pub name: String,
+    pub ticket: Option<Ticket>,
+}
+
+/// Reads one kind of clipboard content, clipboard comes trimmed
+pub trait ClipboardRecognizer {
+    /// Shown in error listing what clipboard can hold
+    fn description(&self) -> &'static str;
+    fn recognize(&self, clipboard: &str) -> Option<Recognized>;
+}
+
+/// `git checkout -b name`, `git switch -c name` and plain `git checkout name`
+/// GitHub gives for issues, also on any line of longer snippet
+struct GitCommand {
+    ticket_patterns: Vec<Regex>,
+}
+
+/// Jira `/browse/PAY-12` and GitHub or GitLab `/issues/12`
+struct IssueUrl;
+
+/// `PAY-12 Fix login`, `[PAY-12] Fix login` or title without ticket
+struct IssueTitle {
+    ticket_patterns: Vec<Regex>,
+}
+
+/// Single word is taken as it is
+struct BareName {
+    ticket_patterns: Vec<Regex>,
+}
+
+impl ClipboardRecognizer for GitCommand {
+    fn description(&self) -> &'static str {
+        "git checkout -b or git switch -c command"
+    }
+
+    fn recognize(&self, clipboard: &str) -> Option<Recognized> {
+        let command_regex = Regex::new(
+            r"^git\s+(?:checkout(?:\s+-[bB])?|switch(?:\s+(?:-[cC]|--create|--force-create))?)\s+([^\s-]\S*)(?:\s+\S+)?$",
+        )
+        .unwrap();
+        let name = clipboard
+            .lines()
+            .find_map(|line| command_regex.captures(line.trim()))?[1]
+            .to_owned();
+        Some(Recognized {
+            ticket: find_ticket(&name, &self.ticket_patterns),
+            name,
+        })
+    }
+}
+
+impl ClipboardRecognizer for IssueUrl {
+    fn description(&self) -> &'static str {
+        "Jira, GitHub or GitLab issue URL"
+    }
+
+    fn recognize(&self, clipboard: &str) -> Option<Recognized> {
+        let jira_regex =
+            Regex::new(r"^https?://\S+(?:/browse/|[?&]selectedIssue=)([A-Za-z][A-Za-z0-9]+-\d+)")
+                .unwrap();
+        let issue_regex = Regex::new(r"^https?://\S+/issues/(\d+)(?:[/?#]\S*)?$").unwrap();
+
+        let ticket = if let Some(captures) = jira_regex.captures(clipboard) {
+            Ticket::from_id(&captures[1])
+        } else {
+            Ticket::from_id(&issue_regex.captures(clipboard)?[1])
+        };
+        Some(Recognized {
+            name: ticket.to_string(),
+            ticket: Some(ticket),
+        })
+    }
+}
+
+impl ClipboardRecognizer for IssueTitle {
+    fn description(&self) -> &'static str {
+        "issue title like PAY-12 Fix login"
+    }
+
+    fn recognize(&self, clipboard: &str) -> Option<Recognized> {
+        if clipboard.contains('\n') || !clipboard.contains(char::is_whitespace) {
+            return None;
+        }
+        let ticket = find_ticket(clipboard, &self.ticket_patterns);
+        let title = match &ticket {
+            // Ticket is put first in its normalized form
+            Some(ticket) => Regex::new(&format!("(?i){}", regex::escape(&ticket.to_string())))
+                .unwrap()
+                .replace(clipboard, "")
+                .into_owned(),
+            None => clipboard.to_owned(),
+        };
+        let title = Filter::Slug.apply(&title);
+        let name = match &ticket {
+            Some(ticket) if title.is_empty() => ticket.to_string(),
+            Some(ticket) => format!("{}-{}", ticket, title),
+            None => title,
+        };
+        if name.is_empty() {
+            return None;
+        }
+        Some(Recognized { name, ticket })
+    }
+}
+
+impl ClipboardRecognizer for BareName {
+    fn description(&self) -> &'static str {
+        "branch name"
+    }
+
+    fn recognize(&self, clipboard: &str) -> Option<Recognized> {
+        if clipboard.is_empty()
+            || clipboard.contains(char::is_whitespace)
+            || clipboard.contains("://")
+        {
+            return None;
+        }
+        Some(Recognized {
+            name: clipboard.to_owned(),
+            ticket: find_ticket(clipboard, &self.ticket_patterns),
+        })
+    }
+}
+
+/// Recognizers used by bp, in order they are tried
+pub fn default_recognizers(ticket_patterns: Vec<Regex>) -> Vec<Box<dyn ClipboardRecognizer>> {
+    vec![
+        Box::new(GitCommand {
+            ticket_patterns: ticket_patterns.clone(),
+        }),
+        Box::new(IssueUrl),
+        Box::new(IssueTitle {
+            ticket_patterns: ticket_patterns.clone(),
+        }),
+        Box::new(BareName { ticket_patterns }),
+    ]
+}
+
+/// First recognizer that can read the clipboard wins
+pub fn recognize(
+    clipboard: &str,
+    recognizers: &[Box<dyn ClipboardRecognizer>],
+) -> Result<Recognized> {
+    let clipboard = clipboard.trim();
+    recognizers
+        .iter()
+        .find_map(|recognizer| recognizer.recognize(clipboard))
+        .ok_or_else(|| {
+            let accepted: Vec<&str> = recognizers
+                .iter()
+                .map(|recognizer| recognizer.description())
+                .collect();
+            anyhow!(
+                "Couldn't get branch name from your clipboard: \n {} \n \
+                It can hold: {}",
+                clipboard,
+                accepted.join(", ")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticket::ticket_patterns;
+
+    fn name_and_ticket(clipboard: &str) -> (String, Option<String>) {
+        let recognized = recognize(
+            clipboard,
+            &default_recognizers(ticket_patterns(&[]).unwrap()),
+        )
+        .unwrap();
+        (
+            recognized.name,
+            recognized.ticket.map(|ticket| ticket.to_string()),
+        )
+    }
+
+    #[test]
+    fn recognizes_each_clipboard_format() {
+        let cases = [
+            (
+                "git checkout -b PAY-12-fix-login\n",
+                "PAY-12-fix-login",
+                Some("PAY-12"),
+            ),
+            (
+                "git switch --create fix-login origin/main",
+                "fix-login",
+                None,
+            ),
+            (
+                "git fetch origin\ngit checkout 12-fix-login",
+                "12-fix-login",
+                None,
+            ),
+            (
+                "https://acme.atlassian.net/browse/pay-12",
+                "PAY-12",
+                Some("PAY-12"),
+            ),
+            (
+                "https://github.com/acme/app/issues/12#top",
+                "12",
+                Some("12"),
+            ),
+            (
+                "[PAY-12] Fix login, again!",
+                "PAY-12-fix-login-again",
+                Some("PAY-12"),
+            ),
+            ("Fix login page", "fix-login-page", None),
+            ("  feature/OPS-7 ", "feature/OPS-7", Some("OPS-7")),
+        ];
+        for (clipboard, name, ticket) in cases {
+            assert_eq!(
+                name_and_ticket(clipboard),
+                (name.to_owned(), ticket.map(|ticket| ticket.to_owned())),
+                "{}",
+                clipboard
+            );
+        }
+    }
+
+    #[test]
+    fn lists_accepted_formats_when_nothing_recognizes() {
+        let error = recognize("first line\nsecond line", &default_recognizers(vec![]))
+            .unwrap_err()
+            .to_string();
+        assert!(error.ends_with(
+            "It can hold: git checkout -b or git switch -c command, \
+            Jira, GitHub or GitLab issue URL, issue title like PAY-12 Fix login, branch name"
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::template::{variant::PART_SEPARATOR, TemplateVariant};
+
+/// Commit message in git's layout: subject, blank line, body, blank line, trailers
+#[derive(Debug, PartialEq)]
+pub struct CommitMessage {
+    pub subject: String,
+    pub body: Option<String>,
+    pub trailers: Vec<(String, String)>,
+}
+
+impl CommitMessage {
+    /// Splits template rendered as a whole into its parts, body is wrapped
+    /// and trailers that ended up empty are left out
+    pub fn from_rendered(variant: &TemplateVariant, rendered: &str, body_width: usize) -> Self {
+        let mut parts = rendered.split(PART_SEPARATOR);
+        let subject = parts.next().unwrap_or_default().trim().to_owned();
+        let body = match variant.body {
+            Some(_) => parts.next().map(|body| wrap(body.trim(), body_width)),
+            None => None,
+        }
+        .filter(|body| !body.is_empty());
+        let trailers = variant
+            .trailers
+            .keys()
+            .zip(parts)
+            .map(|(key, value)| (key.to_owned(), value.trim().to_owned()))
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+
+        CommitMessage {
+            subject,
+            body,
+            trailers,
+        }
+    }
+
+    /// Trailer with the same key and value as one already there isn't repeated
+    pub fn add_trailers(&mut self, trailers: Vec<(String, String)>) {
+        for (key, value) in trailers {
+            let is_duplicate = self.trailers.iter().any(|(existing_key, existing_value)| {
+                existing_key.eq_ignore_ascii_case(&key) && *existing_value == value
+            });
+            if !is_duplicate {
+                self.trailers.push((key, value));
+            }
+        }
+    }
+}
+
+impl fmt::Display for CommitMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.subject)?;
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{}", body)?;
+        }
+        if !self.trailers.is_empty() {
+            writeln!(f)?;
+            for (key, value) in &self.trailers {
+                write!(f, "\n{}: {}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Accepts `Key: value` like git and `Key=value` like values of templates
+pub fn parse_trailer(trailer: &str) -> Result<(String, String)> {
+    let trailer_regex = Regex::new(r"(?s)^([A-Za-z0-9][A-Za-z0-9-]*)\s*[:=]\s*(.*)$").unwrap();
+    let captures = trailer_regex.captures(trailer.trim()).ok_or_else(|| {
+        anyhow!(
+            "Trailer should look like `Key: value` or `Key=value`, got `{}`",
+            trailer
+        )
+    })?;
+    Ok((captures[1].to_owned(), captures[2].trim().to_owned()))
+}
+
+/// Wraps each paragraph to width, width of 0 leaves text as it is.
+/// Indented lines are kept as they are, so code and quotes aren't broken,
+/// list items are wrapped with indent under their text
+pub fn wrap(text: &str, width: usize) -> String {
+    if width == 0 {
+        return text.to_owned();
+    }
+    let list_item_regex = Regex::new(r"^([-*]|\d+[.)])\s+").unwrap();
+
+    let mut lines: Vec<String> = vec![];
+    let mut paragraph: Vec<&str> = vec![];
+    let mut indent = String::new();
+    let flush = |paragraph: &mut Vec<&str>, indent: &str, lines: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            lines.extend(wrap_words(&paragraph.join(" "), width, indent));
+            paragraph.clear();
+        }
+    };
+
+    for line in text.lines() {
+        if line.trim().is_empty() || line.starts_with([' ', '\t']) {
+            flush(&mut paragraph, &indent, &mut lines);
+            lines.push(line.trim_end().to_owned());
+            continue;
+        }
+        if let Some(marker) = list_item_regex.find(line) {
+            flush(&mut paragraph, &indent, &mut lines);
+            indent = " ".repeat(marker.end());
+        } else if paragraph.is_empty() {
+            indent = String::new();
+        }
+        paragraph.push(line.trim());
+    }
+    flush(&mut paragraph, &indent, &mut lines);
+
+    lines.join("\n")
+}
+
+/// Words longer than width, e.g. links, get a line of their own
+fn wrap_words(text: &str, width: usize, indent: &str) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if line.is_empty() {
+            if !lines.is_empty() {
+                line.push_str(indent);
+            }
+        } else {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_paragraphs_and_list_items() {
+        let text = "This change makes login work again when token has expired meanwhile.\n\n\
+            - first item that is long enough to be wrapped\n\
+            - second\n\n    let code = \"kept as it is even when it is long\";";
+        assert_eq!(
+            wrap(text, 30),
+            "This change makes login work\nagain when token has expired\nmeanwhile.\n\n\
+            - first item that is long\n  enough to be wrapped\n- second\n\n    \
+            let code = \"kept as it is even when it is long\";"
+        );
+        assert_eq!(wrap("no wrapping at all", 0), "no wrapping at all");
+    }
+
+    #[test]
+    fn renders_parts_and_merges_trailers() {
+        let variant: TemplateVariant = serde_json::from_value(serde_json::json!({
+            "template": "fix: {}",
+            "body": "{body?}",
+            "trailers": {"Refs": "{ticket}", "Reviewed-by": "{reviewer?}"}
+        }))
+        .unwrap();
+        let rendered = format!(
+            "fix: login{}Token was checked too late{}PAY-1{}",
+            PART_SEPARATOR, PART_SEPARATOR, PART_SEPARATOR
+        );
+
+        let mut message = CommitMessage::from_rendered(&variant, &rendered, 72);
+        message.add_trailers(vec![
+            parse_trailer("refs: PAY-1").unwrap(),
+            parse_trailer("Co-authored-by=Ann <ann@example.com>").unwrap(),
+        ]);
+        assert_eq!(
+            message.to_string(),
+            "fix: login\n\nToken was checked too late\n\n\
+            Refs: PAY-1\nCo-authored-by: Ann <ann@example.com>"
+        );
+        assert!(parse_trailer("no trailer").is_err());
+    }
+}
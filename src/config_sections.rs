@@ -0,0 +1,376 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+
+use crate::{
+    cli::{ConfigSection, SectionCommand, SectionOperation},
+    file_utils::config_layers::LayerKind,
+    git_config::{Data, GitConfig},
+    template::TemplateVariant,
+};
+
+impl ConfigSection {
+    /// Name of the section in config file
+    pub fn key(&self) -> &'static str {
+        match self {
+            ConfigSection::CommitTemplates => "commit_template_variants",
+            ConfigSection::BranchTemplates => "branch_template_variants",
+            ConfigSection::BranchPrefixes => "branch_prefix_variants",
+            ConfigSection::AutocompleteValues => "autocomplete_values",
+        }
+    }
+
+    fn item_name(&self) -> &'static str {
+        match self {
+            ConfigSection::CommitTemplates => "commit template",
+            ConfigSection::BranchTemplates => "branch template",
+            ConfigSection::BranchPrefixes => "branch prefix",
+            ConfigSection::AutocompleteValues => "autocomplete value",
+        }
+    }
+}
+
+fn unknown_key_error<V>(
+    section: &ConfigSection,
+    key: &str,
+    map: &IndexMap<String, V>,
+) -> anyhow::Error {
+    let available: Vec<&str> = map.keys().map(|key| key.as_str()).collect();
+    if available.is_empty() {
+        return anyhow!(
+            "There is no {} `{}`, none are set",
+            section.item_name(),
+            key
+        );
+    }
+    anyhow!(
+        "There is no {} `{}`, available: {}",
+        section.item_name(),
+        key,
+        available.join(", ")
+    )
+}
+
+/// Entry used by commands taking `-k`, error lists the keys to pick from
+pub fn get_entry<'a, V>(
+    section: &ConfigSection,
+    map: &'a IndexMap<String, V>,
+    key: &str,
+) -> Result<&'a V> {
+    map.get(key)
+        .ok_or_else(|| unknown_key_error(section, key, map))
+}
+
+fn list_entries<V: Display>(map: &IndexMap<String, V>) -> String {
+    map.iter()
+        .map(|(key, value)| format!("{}: {}", key, value))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Changes map in place, rename keeps the position of entry.
+/// Copy takes value from merged config, so repo entries can be copied to global one
+fn change_map<V: Clone>(
+    section: &ConfigSection,
+    map: &mut IndexMap<String, V>,
+    merged: &IndexMap<String, V>,
+    operation: &SectionOperation,
+) -> Result<()> {
+    match operation {
+        SectionOperation::List | SectionOperation::Get(_) => {}
+        SectionOperation::Delete(key) => {
+            map.shift_remove(key)
+                .ok_or_else(|| unknown_key_error(section, key, map))?;
+        }
+        SectionOperation::Rename { from, to } | SectionOperation::Copy { from, to } => {
+            let is_copy = matches!(operation, SectionOperation::Copy { .. });
+            let source = if is_copy { merged } else { &*map };
+            let value = source
+                .get(from)
+                .cloned()
+                .ok_or_else(|| unknown_key_error(section, from, source))?;
+            if map.contains_key(to) || merged.contains_key(to) {
+                return Err(anyhow!(
+                    "There already is {} `{}`, delete it first",
+                    section.item_name(),
+                    to
+                ));
+            }
+            if is_copy {
+                map.insert(to.to_owned(), value);
+                return Ok(());
+            }
+            let index = map.get_index_of(from).unwrap();
+            map.shift_remove(from);
+            map.shift_insert(index, to.to_owned(), value);
+        }
+    }
+    Ok(())
+}
+
+/// Autocomplete values are addressed by their position, starting from 1
+fn values_as_map(values: &[String]) -> IndexMap<String, String> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| ((index + 1).to_string(), value.to_owned()))
+        .collect()
+}
+
+/// Rename moves value to given position, copy inserts it there
+fn change_values(values: &mut Vec<String>, operation: &SectionOperation) -> Result<()> {
+    let section = ConfigSection::AutocompleteValues;
+    let position = |key: &String, max: usize| {
+        key.parse::<usize>()
+            .ok()
+            .filter(|position| (1..=max).contains(position))
+            .map(|position| position - 1)
+            .ok_or_else(|| unknown_key_error(&section, key, &values_as_map(values)))
+    };
+
+    match operation {
+        SectionOperation::List | SectionOperation::Get(_) => {}
+        SectionOperation::Delete(key) => {
+            values.remove(position(key, values.len())?);
+        }
+        SectionOperation::Rename { from, to } => {
+            let from = position(from, values.len())?;
+            let to = position(to, values.len())?;
+            let value = values.remove(from);
+            values.insert(to, value);
+        }
+        SectionOperation::Copy { from, to } => {
+            let from = position(from, values.len())?;
+            let to = position(to, values.len() + 1)?;
+            values.insert(to, values[from].to_owned());
+        }
+    }
+    Ok(())
+}
+
+fn change_section(
+    data: &mut Data,
+    merged: &Data,
+    section: &ConfigSection,
+    operation: &SectionOperation,
+) -> Result<()> {
+    match section {
+        ConfigSection::CommitTemplates => change_map(
+            section,
+            &mut data.commit_template_variants,
+            &merged.commit_template_variants,
+            operation,
+        ),
+        ConfigSection::BranchTemplates => change_map(
+            section,
+            &mut data.branch_template_variants,
+            &merged.branch_template_variants,
+            operation,
+        ),
+        ConfigSection::BranchPrefixes => change_map(
+            section,
+            &mut data.branch_prefix_variants,
+            &merged.branch_prefix_variants,
+            operation,
+        ),
+        ConfigSection::AutocompleteValues => {
+            let mut values = data.autocomplete_values.take().unwrap_or_default();
+            let result = change_values(&mut values, operation);
+            data.autocomplete_values = Some(values).filter(|values| !values.is_empty());
+            result
+        }
+    }
+}
+
+/// Templates are shown with their description, args and tags
+fn templates_as_text(variants: &IndexMap<String, TemplateVariant>) -> IndexMap<String, String> {
+    variants
+        .iter()
+        .map(|(key, variant)| (key.to_owned(), variant.to_string()))
+        .collect()
+}
+
+fn section_entries(data: &Data, section: &ConfigSection) -> IndexMap<String, String> {
+    match section {
+        ConfigSection::CommitTemplates => templates_as_text(&data.commit_template_variants),
+        ConfigSection::BranchTemplates => templates_as_text(&data.branch_template_variants),
+        ConfigSection::BranchPrefixes => data.branch_prefix_variants.to_owned(),
+        ConfigSection::AutocompleteValues => {
+            values_as_map(data.autocomplete_values.as_deref().unwrap_or_default())
+        }
+    }
+}
+
+impl GitConfig {
+    /// `list` and `get` show merged config, changes go to global config only
+    pub fn run_section_command(&mut self, command: SectionCommand) -> Result<()> {
+        let SectionCommand { section, operation } = command;
+        let entries = section_entries(&self.data, &section);
+
+        match &operation {
+            SectionOperation::List => {
+                println!("{}", list_entries(&entries));
+                return Ok(());
+            }
+            SectionOperation::Get(key) => {
+                let value = entries
+                    .get(key)
+                    .ok_or_else(|| unknown_key_error(&section, key, &entries))?;
+                println!("{}", value);
+                return Ok(());
+            }
+            SectionOperation::Delete(key) | SectionOperation::Rename { from: key, .. } => {
+                if !matches!(section, ConfigSection::AutocompleteValues)
+                    && self.is_defined_in(LayerKind::Repo, section.key(), Some(key))
+                    && !self.is_defined_in(LayerKind::Global, section.key(), Some(key))
+                {
+                    return Err(anyhow!(
+                        "The {} `{}` comes from repo config, change it there",
+                        section.item_name(),
+                        key
+                    ));
+                }
+            }
+            SectionOperation::Copy { .. } => {}
+        }
+        // Autocomplete values of repo config replace global ones as a whole
+        if matches!(section, ConfigSection::AutocompleteValues)
+            && self.is_defined_in(LayerKind::Repo, section.key(), None)
+        {
+            return Err(anyhow!(
+                "Autocomplete values come from repo config, change them there"
+            ));
+        }
+
+        let merged = self.data.clone();
+        self.update(|data| change_section(data, &merged, &section, &operation))?;
+        println!("{}", list_entries(&section_entries(&self.data, &section)));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn templates() -> IndexMap<String, String> {
+        IndexMap::from_iter([
+            ("default".to_owned(), "{}".to_owned()),
+            ("fix".to_owned(), "fix: {}".to_owned()),
+            ("feat".to_owned(), "feat: {}".to_owned()),
+        ])
+    }
+
+    fn rename(from: &str, to: &str) -> SectionOperation {
+        SectionOperation::Rename {
+            from: from.to_owned(),
+            to: to.to_owned(),
+        }
+    }
+
+    #[test]
+    fn renames_entry_in_place() {
+        let mut map = templates();
+        change_map(
+            &ConfigSection::CommitTemplates,
+            &mut map,
+            &templates(),
+            &rename("fix", "bugfix"),
+        )
+        .unwrap();
+        assert_eq!(
+            map.keys().collect::<Vec<&String>>(),
+            vec!["default", "bugfix", "feat"]
+        );
+        assert_eq!(map.get("bugfix").unwrap(), "fix: {}");
+    }
+
+    #[test]
+    fn fails_on_unknown_or_taken_key() {
+        let mut map = templates();
+        let error = change_map(
+            &ConfigSection::CommitTemplates,
+            &mut map,
+            &templates(),
+            &SectionOperation::Delete("docs".to_owned()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "There is no commit template `docs`, available: default, fix, feat"
+        );
+        assert!(change_map(
+            &ConfigSection::BranchTemplates,
+            &mut map,
+            &templates(),
+            &rename("fix", "feat")
+        )
+        .is_err());
+        assert_eq!(map, templates());
+    }
+
+    #[test]
+    fn gets_entry_or_fails_on_unknown_key() {
+        let map = templates();
+        assert_eq!(
+            get_entry(&ConfigSection::CommitTemplates, &map, "fix").unwrap(),
+            "fix: {}"
+        );
+        assert_eq!(
+            get_entry(&ConfigSection::BranchTemplates, &map, "docs")
+                .unwrap_err()
+                .to_string(),
+            "There is no branch template `docs`, available: default, fix, feat"
+        );
+        assert_eq!(
+            get_entry(
+                &ConfigSection::CommitTemplates,
+                &IndexMap::<String, String>::new(),
+                "x"
+            )
+            .unwrap_err()
+            .to_string(),
+            "There is no commit template `x`, none are set"
+        );
+    }
+
+    #[test]
+    fn copies_entry_from_merged_config() {
+        let mut map = IndexMap::new();
+        let copy = SectionOperation::Copy {
+            from: "fix".to_owned(),
+            to: "bugfix".to_owned(),
+        };
+        change_map(
+            &ConfigSection::CommitTemplates,
+            &mut map,
+            &templates(),
+            &copy,
+        )
+        .unwrap();
+        assert_eq!(map.get("bugfix").unwrap(), "fix: {}");
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn changes_autocomplete_values_by_position() {
+        let mut values = vec!["web".to_owned(), "api".to_owned(), "cli".to_owned()];
+        change_values(&mut values, &rename("3", "1")).unwrap();
+        assert_eq!(values, vec!["cli", "web", "api"]);
+
+        change_values(
+            &mut values,
+            &SectionOperation::Copy {
+                from: "2".to_owned(),
+                to: "4".to_owned(),
+            },
+        )
+        .unwrap();
+        assert_eq!(values, vec!["cli", "web", "api", "web"]);
+
+        assert!(change_values(&mut values, &SectionOperation::Delete("0".to_owned())).is_err());
+        change_values(&mut values, &SectionOperation::Delete("1".to_owned())).unwrap();
+        assert_eq!(values, vec!["web", "api", "web"]);
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::{anyhow, Error, Result};
+use regex::Regex;
+
+use crate::{
+    cli::ConventionalCommitArguments,
+    commit::run_git_commit,
+    commit_message::{wrap, CommitMessage},
+    git_config::{ConventionalCommits, GitConfig},
+};
+
+/// Types of @commitlint/config-conventional
+pub const DEFAULT_TYPES: [&str; 11] = [
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+pub const DEFAULT_SUBJECT_MAX_LENGTH: usize = 72;
+
+const BREAKING_CHANGE_TRAILER: &str = "BREAKING CHANGE";
+
+/// Types have to fit in the header, so they can't have spaces, parens, `!` or `:`
+pub fn validate_conventional_types(types: &[String]) -> Result<()> {
+    let type_regex = Regex::new(r"^[^\s()!:]+$").unwrap();
+    match types.iter().find(|type_| !type_regex.is_match(type_)) {
+        Some(invalid) => Err(anyhow!(
+            "Type `{}` can't be used, it can't have spaces, parens, ! or :",
+            invalid
+        )),
+        None => Ok(()),
+    }
+}
+
+/// `type(scope)!: description`
+pub fn header(arguments: &ConventionalCommitArguments) -> String {
+    let scope = match &arguments.scope {
+        Some(scope) => format!("({})", scope),
+        None => String::new(),
+    };
+    let breaking = if arguments.breaking || arguments.breaking_change.is_some() {
+        "!"
+    } else {
+        ""
+    };
+    format!(
+        "{}{}{}: {}",
+        arguments.commit_type, scope, breaking, arguments.description
+    )
+}
+
+/// Checks whole message against Conventional Commits 1.0.0 and allowed types,
+/// all problems found are listed at once
+pub fn validate_message(message: &str, rules: &ConventionalCommits) -> Result<()> {
+    let header_regex = Regex::new(
+        r"^(?P<type>[^\s():!]+)(?:\((?P<scope>[^()]*)\))?(?P<breaking>!)?:(?P<space> ?)(?P<description>.*)$",
+    )
+    .unwrap();
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or_default();
+    let mut problems = vec![];
+
+    match header_regex.captures(subject) {
+        None => problems.push(format!(
+            "Subject `{}` doesn't follow Conventional Commits, \
+            expected `type(scope)!: description`",
+            subject
+        )),
+        Some(captures) => {
+            let type_ = &captures["type"];
+            if !rules.types.iter().any(|allowed| allowed == type_) {
+                problems.push(format!(
+                    "Type `{}` isn't allowed, use one of: {}",
+                    type_,
+                    rules.types.join(", ")
+                ));
+            }
+            if let Some(scope) = captures.name("scope") {
+                let scope = scope.as_str();
+                if scope.trim().is_empty() || scope.contains(char::is_whitespace) {
+                    problems.push(format!(
+                        "Scope `{}` has to be a non-empty word like `api` or `login-form`",
+                        scope
+                    ));
+                }
+            }
+            if captures["description"].trim().is_empty() {
+                problems.push("Description after `type: ` can't be empty".to_owned());
+            } else if captures["space"].is_empty() || captures["description"].starts_with(' ') {
+                problems.push("Type has to be followed by `: ` and description".to_owned());
+            }
+        }
+    }
+
+    let subject_length = subject.chars().count();
+    if rules.subject_max_length > 0 && subject_length > rules.subject_max_length {
+        problems.push(format!(
+            "Subject is {} characters long, at most {} are allowed",
+            subject_length, rules.subject_max_length
+        ));
+    }
+
+    if lines.next().is_some_and(|line| !line.trim().is_empty()) {
+        problems.push("Subject has to be followed by an empty line".to_owned());
+    }
+
+    let breaking_footer_regex = Regex::new(r"(?i)^breaking[ -]change\s*:").unwrap();
+    let valid_breaking_footer_regex = Regex::new(r"^BREAKING[ -]CHANGE: \S").unwrap();
+    for line in message.lines().skip(1) {
+        if breaking_footer_regex.is_match(line) && !valid_breaking_footer_regex.is_match(line) {
+            problems.push(format!(
+                "Footer `{}` should look like `BREAKING CHANGE: description`",
+                line
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::msg(format!("\n {}", problems.join("\n "))))
+    }
+}
+
+/// Builds message from typed parts, it's checked again after editing
+pub fn conventional_commit(
+    arguments: ConventionalCommitArguments,
+    config: GitConfig,
+) -> Result<()> {
+    let mut message = CommitMessage {
+        subject: header(&arguments),
+        body: arguments
+            .body
+            .as_deref()
+            .map(|body| wrap(body.trim(), config.data.commit_body_width))
+            .filter(|body| !body.is_empty()),
+        trailers: vec![],
+    };
+    if let Some(breaking_change) = &arguments.breaking_change {
+        message.add_trailers(vec![(
+            BREAKING_CHANGE_TRAILER.to_owned(),
+            breaking_change.trim().to_owned(),
+        )]);
+    }
+
+    let rules = config.data.conventional.clone();
+    run_git_commit(
+        message,
+        arguments.trailers,
+        &arguments.flags,
+        &config,
+        None,
+        &|message| validate_message(message, &rules),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(message: &str) -> String {
+        validate_message(message, &ConventionalCommits::default())
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn accepts_messages_following_spec() {
+        let rules = ConventionalCommits::default();
+        for message in [
+            "fix: prevent racing of requests",
+            "feat(api)!: send an email to the customer when a product is shipped",
+            "chore!: drop support for Node 6\n\nBREAKING CHANGE: use JavaScript features not available in Node 6.",
+            "docs(readme): correct spelling\n\nLonger body\n\nRefs: PAY-12",
+        ] {
+            validate_message(message, &rules).unwrap();
+        }
+    }
+
+    #[test]
+    fn lists_every_problem_found() {
+        assert_eq!(
+            problems("feet(): add login"),
+            "\n Type `feet` isn't allowed, use one of: \
+            feat, fix, docs, style, refactor, perf, test, build, ci, chore, revert\n \
+            Scope `` has to be a non-empty word like `api` or `login-form`"
+        );
+        assert_eq!(
+            problems(&format!(
+                "fix:{}\nbody\n\nbreaking change: yes",
+                "x".repeat(70)
+            )),
+            "\n Type has to be followed by `: ` and description\n \
+            Subject is 74 characters long, at most 72 are allowed\n \
+            Subject has to be followed by an empty line\n \
+            Footer `breaking change: yes` should look like `BREAKING CHANGE: description`"
+        );
+        assert!(problems("add login").contains("doesn't follow Conventional Commits"));
+    }
+
+    #[test]
+    fn builds_header_from_parts() {
+        let arguments = ConventionalCommitArguments {
+            commit_type: "feat".to_owned(),
+            scope: Some("api".to_owned()),
+            breaking: false,
+            breaking_change: Some("tokens are required".to_owned()),
+            description: "require tokens".to_owned(),
+            body: None,
+            flags: Default::default(),
+            trailers: vec![],
+        };
+        assert_eq!(header(&arguments), "feat(api)!: require tokens");
+    }
+}
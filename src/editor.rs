@@ -0,0 +1,175 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::git;
+
+const MESSAGE_FILE_NAME: &str = "GIT_HELPE_RS_EDITMSG";
+
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// Message after editing, kept in a file so it can be passed to `git commit -F`.
+/// File is removed when this is dropped, unless it's kept
+pub struct EditedMessage {
+    pub message: String,
+    pub path: PathBuf,
+    keep: bool,
+}
+
+impl EditedMessage {
+    /// Leaves file in place for `git commit -F` that's run later, e.g. after copying
+    pub fn keep_file(&mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for EditedMessage {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Same lookup as git: GIT_EDITOR, then core.editor, VISUAL and EDITOR through
+/// `git var`, EDITOR alone is used when outside of repository
+fn editor() -> Result<String> {
+    std::env::var("GIT_EDITOR")
+        .ok()
+        .filter(|editor| !editor.is_empty())
+        .or_else(|| git::git_output(&["var", "GIT_EDITOR"]).ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|editor| !editor.is_empty())
+        .ok_or_else(|| anyhow!("No editor found, set GIT_EDITOR or EDITOR"))
+}
+
+/// core.commentChar, `auto` and longer values fall back to `#`
+pub fn comment_char() -> char {
+    let comment_char = git::config_value("core.commentChar").unwrap_or_default();
+    let mut chars = comment_char.chars();
+    match (chars.next(), chars.next()) {
+        (Some(comment_char), None) => comment_char,
+        _ => '#',
+    }
+}
+
+/// Message file lives in .git like COMMIT_EDITMSG, or in temp dir outside of repository
+fn message_path() -> PathBuf {
+    git::git_output(&["rev-parse", "--git-path", MESSAGE_FILE_NAME])
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| std::env::temp_dir().join(MESSAGE_FILE_NAME))
+}
+
+/// Cleans message like `git commit --cleanup=strip`: drops comment lines and
+/// trailing whitespace, squashes blank lines and trims blank lines around.
+/// Diff below scissors line of `git commit -v` is dropped too
+pub fn strip_message(text: &str, comment_char: char) -> String {
+    let scissors = format!("{} {}", comment_char, SCISSORS);
+    let mut lines: Vec<&str> = vec![];
+    for line in text.lines() {
+        if line == scissors {
+            break;
+        }
+        if line.starts_with(comment_char) {
+            continue;
+        }
+        let line = line.trim_end();
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+
+    let mut message = lines.join("\n");
+    if !message.is_empty() {
+        message.push('\n');
+    }
+    message
+}
+
+fn run_editor(editor: &str, path: &Path) -> Result<()> {
+    // Editor can have arguments, e.g. `code --wait`, so it's run by shell as git does
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(editor)
+        .arg(path)
+        .status()
+        .map_err(|err| anyhow!("Couldn't run editor {}: {}", editor, err))?;
+
+    if !status.success() {
+        return Err(anyhow!(
+            "Editor {} failed with {}, aborting",
+            editor,
+            status
+        ));
+    }
+    Ok(())
+}
+
+/// Opens rendered message in editor, fails when it's left empty
+pub fn edit_commit_message(message: &str) -> Result<EditedMessage> {
+    let comment_char = comment_char();
+    let path = message_path();
+
+    let edited = fs::write(
+        &path,
+        format!(
+            "{}\n\n\
+            {c} Please enter the commit message for your changes. Lines starting\n\
+            {c} with '{c}' will be ignored, and an empty message aborts the commit.\n",
+            message.trim_end(),
+            c = comment_char
+        ),
+    )
+    .map_err(anyhow::Error::from)
+    .and_then(|_| run_editor(&editor()?, &path))
+    .and_then(|_| Ok(strip_message(&fs::read_to_string(&path)?, comment_char)))
+    .and_then(|message| {
+        if message.is_empty() {
+            return Err(anyhow!("Aborting commit due to empty commit message"));
+        }
+        fs::write(&path, &message)?;
+        Ok(message)
+    });
+
+    match edited {
+        Ok(message) => Ok(EditedMessage {
+            message,
+            path,
+            keep: false,
+        }),
+        Err(err) => {
+            let _ = fs::remove_file(&path);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_comments_and_blank_lines() {
+        let text =
+            "\n[PAY-1] fix login  \n\n\n# comment\nLonger body\n;not comment\n\n# Please enter\n";
+        assert_eq!(
+            strip_message(text, '#'),
+            "[PAY-1] fix login\n\nLonger body\n;not comment\n"
+        );
+        assert_eq!(strip_message("# only\n\n  \n", '#'), "");
+        assert_eq!(strip_message("; x\nfix\n# y", ';'), "fix\n# y\n");
+        assert_eq!(
+            strip_message(&format!("fix\n# {}\ndiff --git a/x b/x", SCISSORS), '#'),
+            "fix\n"
+        );
+    }
+}
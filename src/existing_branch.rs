@@ -0,0 +1,272 @@
+use std::{
+    fmt,
+    io::{BufRead, Write},
+};
+
+use anyhow::{anyhow, Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    git,
+    interactive::{stdin_is_terminal, Prompter},
+    run_mode::command_line,
+};
+
+/// What bt and bp do when branch they'd create already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExistingBranchAction {
+    /// Offer choices in terminal, fail otherwise
+    #[default]
+    Ask,
+    /// Switch to local branch, or create one tracking remote branch
+    Switch,
+    /// Create branch with first free `-2`, `-3`, ... suffix
+    Suffix,
+    Fail,
+}
+
+impl TryFrom<&str> for ExistingBranchAction {
+    type Error = Error;
+    fn try_from(action: &str) -> Result<Self> {
+        match action {
+            "ask" => Ok(ExistingBranchAction::Ask),
+            "switch" => Ok(ExistingBranchAction::Switch),
+            "suffix" => Ok(ExistingBranchAction::Suffix),
+            "fail" => Ok(ExistingBranchAction::Fail),
+            _ => Err(anyhow!(
+                "Unknown action {} for existing branch, use ask, switch, suffix or fail",
+                action
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExistingBranchAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self {
+            ExistingBranchAction::Ask => "ask",
+            ExistingBranchAction::Switch => "switch",
+            ExistingBranchAction::Suffix => "suffix",
+            ExistingBranchAction::Fail => "fail",
+        };
+        write!(f, "{}", action)
+    }
+}
+
+/// Branch found locally, on a remote or both
+#[derive(Debug, PartialEq)]
+pub struct ExistingBranch {
+    pub name: String,
+    pub local: bool,
+    /// Remote-tracking branch like `origin/name`, origin is preferred
+    pub remote: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Checkout {
+    Create(String),
+    Track { name: String, remote: String },
+    Switch(String),
+}
+
+impl Checkout {
+    pub fn git_args(&self) -> Vec<String> {
+        let args: Vec<&str> = match self {
+            Checkout::Create(name) => vec!["checkout", "-b", name],
+            Checkout::Track { name, remote } => vec!["checkout", "-b", name, "--track", remote],
+            Checkout::Switch(name) => vec!["checkout", name],
+        };
+        args.into_iter().map(|arg| arg.to_owned()).collect()
+    }
+}
+
+/// How choice is shown in menu
+impl fmt::Display for Checkout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Checkout::Create(name) => write!(f, "create new branch {}", name),
+            Checkout::Track { name, remote } => write!(f, "create {} tracking {}", name, remote),
+            Checkout::Switch(name) => write!(f, "switch to existing branch {}", name),
+        }
+    }
+}
+
+fn local_branch_exists(name: &str) -> bool {
+    git::git_output(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("refs/heads/{}", name),
+    ])
+    .is_ok()
+}
+
+fn remote_branches(name: &str) -> Vec<String> {
+    let mut remotes: Vec<String> = git::git_output(&[
+        "for-each-ref",
+        "--format=%(refname:short)",
+        &format!("refs/remotes/*/{}", name),
+    ])
+    .unwrap_or_default()
+    .lines()
+    .map(|remote| remote.to_owned())
+    .collect();
+    remotes.sort_by_key(|remote| !remote.starts_with("origin/"));
+    remotes
+}
+
+/// Taken names can't be used for new branch, neither locally nor on remotes
+fn is_taken(name: &str) -> bool {
+    local_branch_exists(name) || !remote_branches(name).is_empty()
+}
+
+impl ExistingBranch {
+    /// None when there is no such branch anywhere
+    pub fn find(name: &str) -> Option<ExistingBranch> {
+        let local = local_branch_exists(name);
+        let remote = remote_branches(name).into_iter().next();
+        if !local && remote.is_none() {
+            return None;
+        }
+        Some(ExistingBranch {
+            name: name.to_owned(),
+            local,
+            remote,
+        })
+    }
+
+    fn where_found(&self) -> String {
+        match (&self.remote, self.local) {
+            (Some(remote), true) => format!("locally and as {}", remote),
+            (Some(remote), false) => format!("as {}", remote),
+            (None, _) => "locally".to_owned(),
+        }
+    }
+
+    /// Checkouts offered for the branch, in order of menu
+    pub fn choices(&self, is_taken: &dyn Fn(&str) -> bool) -> Vec<Checkout> {
+        let existing = match &self.remote {
+            Some(remote) if !self.local => Checkout::Track {
+                name: self.name.to_owned(),
+                remote: remote.to_owned(),
+            },
+            _ => Checkout::Switch(self.name.to_owned()),
+        };
+        vec![
+            existing,
+            Checkout::Create(suffixed_name(&self.name, is_taken)),
+        ]
+    }
+
+    pub fn error(&self) -> Error {
+        anyhow!(
+            "Branch {} already exists {} \n \
+            Set what to do then with set-existing-branch ask|switch|suffix|fail, \n \
+            or with --existing of set-branch-template",
+            self.name,
+            self.where_found()
+        )
+    }
+
+    /// Question asked before choices when action is ask
+    pub fn question(&self) -> String {
+        format!("Branch {} already exists {}", self.name, self.where_found())
+    }
+
+    /// Shown instead of asking when nothing is going to run, e.g. in dry run
+    fn choices_to_ask(&self, choices: &[Checkout]) -> String {
+        let choices: Vec<String> = choices
+            .iter()
+            .map(|choice| format!("  {}: {}", choice, command_line("git", &choice.git_args())))
+            .collect();
+        format!(
+            "{}, you'd be asked to pick one of: \n{}",
+            self.question(),
+            choices.join("\n")
+        )
+    }
+}
+
+/// First of `name-2`, `name-3`, ... that isn't taken
+fn suffixed_name(name: &str, is_taken: &dyn Fn(&str) -> bool) -> String {
+    (2..)
+        .map(|suffix| format!("{}-{}", name, suffix))
+        .find(|suffixed| !is_taken(suffixed))
+        .unwrap()
+}
+
+/// Checkout of branch with given name, None when asked and aborted.
+/// Without prompter choices of ask are only listed and None is returned
+pub fn resolve_checkout<R: BufRead, W: Write>(
+    name: &str,
+    action: ExistingBranchAction,
+    prompter: Option<&mut Prompter<R, W>>,
+) -> Result<Option<Checkout>> {
+    let Some(existing) = ExistingBranch::find(name) else {
+        return Ok(Some(Checkout::Create(name.to_owned())));
+    };
+    let mut choices = existing.choices(&is_taken);
+    match action {
+        ExistingBranchAction::Switch => Ok(Some(choices.remove(0))),
+        ExistingBranchAction::Suffix => Ok(Some(choices.remove(1))),
+        ExistingBranchAction::Ask if stdin_is_terminal() => {
+            let Some(prompter) = prompter else {
+                println!("{}", existing.choices_to_ask(&choices));
+                return Ok(None);
+            };
+            let mut options: Vec<String> =
+                choices.iter().map(|choice| choice.to_string()).collect();
+            options.push("abort".to_owned());
+            let picked = prompter.pick_option(&existing.question(), &options)?;
+            Ok((picked < choices.len()).then(|| choices.remove(picked)))
+        }
+        ExistingBranchAction::Ask | ExistingBranchAction::Fail => Err(existing.error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offers_to_track_remote_branch_or_use_free_suffix() {
+        let taken = |name: &str| ["feat/x", "feat/x-2"].contains(&name);
+        let existing = ExistingBranch {
+            name: "feat/x".to_owned(),
+            local: false,
+            remote: Some("origin/feat/x".to_owned()),
+        };
+        let choices = existing.choices(&taken);
+        assert_eq!(
+            choices
+                .iter()
+                .map(|choice| choice.git_args().join(" "))
+                .collect::<Vec<String>>(),
+            vec![
+                "checkout -b feat/x --track origin/feat/x",
+                "checkout -b feat/x-3"
+            ]
+        );
+
+        let existing = ExistingBranch {
+            local: true,
+            ..existing
+        };
+        assert_eq!(
+            existing.choices(&taken)[0].to_string(),
+            "switch to existing branch feat/x"
+        );
+        assert_eq!(
+            existing.choices_to_ask(&existing.choices(&taken)),
+            "Branch feat/x already exists locally and as origin/feat/x, \
+            you'd be asked to pick one of: \n  \
+            switch to existing branch feat/x: git checkout feat/x\n  \
+            create new branch feat/x-3: git checkout -b feat/x-3"
+        );
+        assert!(existing
+            .error()
+            .to_string()
+            .starts_with("Branch feat/x already exists locally and as origin/feat/x"));
+    }
+}
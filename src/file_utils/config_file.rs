@@ -1,17 +1,265 @@
-use std::path::PathBuf;
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
 
+use anyhow::{anyhow, Result};
+
+use super::config_format::ConfigFormat;
+use crate::git;
+
+const GLOBAL_CONFIG_FILE_NAME: &str = ".git-helpe-rs-config";
+
+/// How many previous versions of config are kept as `.bak`, `.bak.1`, ...
+const BACKUP_COUNT: usize = 3;
+
+/// Looked up in repository root first and then in .git directory
+pub const REPO_CONFIG_FILE_NAMES: [&str; 4] = [
+    ".git-helpe-rs.toml",
+    ".git-helpe-rs.yaml",
+    ".git-helpe-rs.yml",
+    ".git-helpe-rs.json",
+];
+
+/// Global config can be `.git-helpe-rs-config` (JSON) or the same name
+/// with one of format extensions, e.g. `.git-helpe-rs-config.toml`
 pub fn get_path_to_config(path: Option<PathBuf>) -> PathBuf {
     if let Some(path) = path {
         return path;
     }
 
-    let mut home = if let Ok(home) = std::env::var("XDG_CONFIG_HOME") {
+    let home = if let Ok(home) = std::env::var("XDG_CONFIG_HOME") {
         PathBuf::from(home)
     } else if let Ok(home) = std::env::var("HOME") {
         PathBuf::from(home)
     } else {
         panic!("Couldn't find home directory")
     };
-    home.push(".git-helpe-rs-config");
-    return home;
+
+    let default_path = home.join(GLOBAL_CONFIG_FILE_NAME);
+    ConfigFormat::NAMES
+        .iter()
+        .map(|extension| with_added_extension(&default_path, extension))
+        .find(|path| path.is_file())
+        .unwrap_or(default_path)
+}
+
+/// Config that can be committed to repository and shared with the team
+pub fn get_path_to_repo_config() -> Option<PathBuf> {
+    let top_level = git::git_output(&["rev-parse", "--show-toplevel"]).ok()?;
+    let git_dir = git::git_output(&["rev-parse", "--absolute-git-dir"]).ok()?;
+
+    [top_level, git_dir]
+        .iter()
+        .flat_map(|dir| {
+            REPO_CONFIG_FILE_NAMES
+                .iter()
+                .map(move |file_name| PathBuf::from(dir).join(file_name))
+        })
+        .find(|path| path.is_file())
+}
+
+fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+/// Path of config converted to given format, `.git-helpe-rs-config` gets
+/// the extension added and `.git-helpe-rs.json` has it replaced
+pub fn get_converted_config_path(path: &Path, format: ConfigFormat) -> PathBuf {
+    match path.extension() {
+        Some(extension) if ConfigFormat::from_name(&extension.to_string_lossy()).is_some() => {
+            path.with_extension(format.extension())
+        }
+        _ => with_added_extension(path, format.extension()),
+    }
+}
+
+/// Writes config in new format and moves the old file to `<old name>.<date>.bak`,
+/// so it's not picked up anymore. Returns new path and path of the old file
+pub fn convert_config_file(path: &Path, format: ConfigFormat) -> Result<(PathBuf, PathBuf)> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| anyhow!("Couldn't read config {}: {}", path.display(), err))?;
+    let value = ConfigFormat::from_path(path).parse(&contents)?;
+
+    let new_path = get_converted_config_path(path, format);
+    if new_path == path {
+        return Err(anyhow!("Config {} is already {}", path.display(), format));
+    }
+    if new_path.exists() {
+        return Err(anyhow!(
+            "{} already exists, remove it first",
+            new_path.display()
+        ));
+    }
+
+    write_atomically(&new_path, &format.serialize(&value)?)?;
+    // Dated, so it isn't mistaken for one of rotating backups
+    let old_path = with_added_extension(
+        path,
+        &chrono::Local::now().format("%Y%m%d%H%M%S.bak").to_string(),
+    );
+    fs::rename(path, &old_path)?;
+    Ok((new_path, old_path))
+}
+
+/// Advisory lock held for the whole read-modify-write of config,
+/// released and removed when dropped
+pub struct ConfigLock {
+    file: File,
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(opened), Ok(current)) => opened.dev() == current.dev() && opened.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_file: &File, path: &Path) -> bool {
+    path.exists()
+}
+
+impl ConfigLock {
+    /// Blocks until other git-helpe-rs process releases the config,
+    /// separate lock file is used as config itself is replaced on write
+    pub fn acquire(config_path: &Path) -> Result<ConfigLock> {
+        if let Some(dir) = config_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lock_path = with_added_extension(config_path, "lock");
+        loop {
+            let file = File::options()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&lock_path)
+                .map_err(|err| anyhow!("Couldn't open lock {}: {}", lock_path.display(), err))?;
+            file.lock()
+                .map_err(|err| anyhow!("Couldn't lock {}: {}", lock_path.display(), err))?;
+
+            // Previous holder removes lock file before releasing it,
+            // so lock taken on a removed file is taken again on a new one
+            if is_same_file(&file, &lock_path) {
+                return Ok(ConfigLock {
+                    file,
+                    path: lock_path,
+                });
+            }
+        }
+    }
+}
+
+impl Drop for ConfigLock {
+    /// Removed while still locked, so nobody can lock it in between
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        let _ = self.file.unlock();
+    }
+}
+
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => with_added_extension(path, "bak"),
+        index => with_added_extension(path, &format!("bak.{}", index)),
+    }
+}
+
+/// Newest backup is `.bak`, the oldest one falls off
+fn rotate_backups(path: &Path) -> Result<()> {
+    for index in (1..BACKUP_COUNT).rev() {
+        let older = backup_path(path, index - 1);
+        if older.exists() {
+            fs::rename(older, backup_path(path, index))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 0))?;
+    Ok(())
+}
+
+/// Writes to a temp file next to config and renames it over the original,
+/// so config is never left half written
+pub fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp_path = with_added_extension(path, &format!("{}.tmp", std::process::id()));
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .map_err(anyhow::Error::from)
+        .and_then(|_| {
+            if path.exists() {
+                rotate_backups(path)?;
+            }
+            Ok(fs::rename(&temp_path, path)?)
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.map_err(|err| anyhow!("Couldn't write config {}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("git-helpe-rs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join(".git-helpe-rs-config")
+    }
+
+    #[test]
+    fn parallel_locked_updates_are_not_lost() {
+        let path = temp_config_path("lock");
+        write_atomically(&path, "0").unwrap();
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..5 {
+                        let _lock = ConfigLock::acquire(&path).unwrap();
+                        let count: u32 = fs::read_to_string(&path).unwrap().parse().unwrap();
+                        write_atomically(&path, &(count + 1).to_string()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "40");
+        assert_eq!(fs::read_to_string(backup_path(&path, 0)).unwrap(), "39");
+        assert_eq!(fs::read_to_string(backup_path(&path, 2)).unwrap(), "37");
+        assert!(!backup_path(&path, 3).exists());
+        assert!(!with_added_extension(&path, "lock").exists());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn converted_path_keeps_file_name() {
+        assert_eq!(
+            get_converted_config_path(Path::new("/home/.git-helpe-rs-config"), ConfigFormat::Toml),
+            PathBuf::from("/home/.git-helpe-rs-config.toml")
+        );
+        assert_eq!(
+            get_converted_config_path(Path::new("/repo/.git-helpe-rs.json"), ConfigFormat::Yaml),
+            PathBuf::from("/repo/.git-helpe-rs.yaml")
+        );
+    }
 }
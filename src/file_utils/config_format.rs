@@ -0,0 +1,260 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde_json::Value;
+
+/// Format of config file, picked by its extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+/// Where and why config file couldn't be parsed, line and column are 1-based
+#[derive(Debug, PartialEq)]
+pub struct ConfigParseError {
+    pub path: Option<PathBuf>,
+    pub format: ConfigFormat,
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl fmt::Display for ConfigParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Couldn't parse {} config", self.format)?;
+        if let Some(path) = &self.path {
+            write!(f, " {}", path.display())?;
+        }
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " at line {}, column {}", line, column)?;
+        }
+        write!(
+            f,
+            ": {} \n \
+            Nothing will be written to it, fix it by hand \n \
+            or run `git-helpe-rs config repair` (--repo for repository config) to keep valid sections",
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ConfigParseError {}
+
+impl ConfigParseError {
+    fn new(format: ConfigFormat, message: String, position: Option<(usize, usize)>) -> Self {
+        ConfigParseError {
+            path: None,
+            format,
+            message,
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+        }
+    }
+
+    pub fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+}
+
+/// serde_json and serde_yaml put position into message on their own
+fn without_position(message: &str) -> String {
+    Regex::new(r" at line \d+ column \d+")
+        .unwrap()
+        .replace_all(message, "")
+        .into_owned()
+}
+
+fn position_of_offset(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+impl ConfigFormat {
+    pub const NAMES: [&'static str; 4] = ["json", "toml", "yaml", "yml"];
+
+    pub fn from_name(name: &str) -> Option<ConfigFormat> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Files without known extension, like the default `.git-helpe-rs-config`, are JSON
+    pub fn from_path(path: &Path) -> ConfigFormat {
+        path.extension()
+            .and_then(|extension| ConfigFormat::from_name(&extension.to_string_lossy()))
+            .unwrap_or(ConfigFormat::Json)
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+        }
+    }
+
+    pub fn parse(&self, contents: &str) -> Result<Value, ConfigParseError> {
+        match self {
+            ConfigFormat::Json => serde_json::from_str(contents).map_err(|err| {
+                let position = Some((err.line(), err.column())).filter(|(line, _)| *line > 0);
+                ConfigParseError::new(*self, without_position(&err.to_string()), position)
+            }),
+            ConfigFormat::Toml => toml::from_str(contents).map_err(|err| {
+                let position = err
+                    .span()
+                    .map(|span| position_of_offset(contents, span.start));
+                ConfigParseError::new(*self, err.message().to_owned(), position)
+            }),
+            ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|err| {
+                let position = err
+                    .location()
+                    .map(|location| (location.line(), location.column()));
+                ConfigParseError::new(*self, without_position(&err.to_string()), position)
+            }),
+        }
+    }
+
+    /// Empty file is an empty config, anything but a map of sections is an error
+    pub fn parse_config(&self, contents: &str) -> Result<Value, ConfigParseError> {
+        if contents.trim().is_empty() {
+            return Ok(Value::Object(Default::default()));
+        }
+        match self.parse(contents)? {
+            Value::Object(object) => Ok(Value::Object(object)),
+            Value::Null => Ok(Value::Object(Default::default())),
+            _ => Err(ConfigParseError::new(
+                *self,
+                "config should be a map of sections".to_owned(),
+                Some((1, 1)),
+            )),
+        }
+    }
+
+    /// Pretty-printed, keys stay in the order they have in value
+    pub fn serialize(&self, value: &Value) -> Result<String> {
+        // TOML has no null, missing key means the same for config
+        let value = without_nulls(value);
+        let contents = match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(&value)? + "\n",
+            ConfigFormat::Toml => toml::to_string_pretty(&value)?,
+            ConfigFormat::Yaml => serde_yaml::to_string(&value)?,
+        };
+
+        if self.parse(&contents)? != value {
+            return Err(anyhow!(
+                "Config can't be written as {} without losing values",
+                self
+            ));
+        }
+        Ok(contents)
+    }
+}
+
+fn without_nulls(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.to_owned(), without_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(array) => Value::Array(array.iter().map(without_nulls).collect()),
+        value => value.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn picks_format_by_extension() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("/home/.git-helpe-rs-config")),
+            ConfigFormat::Json
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new(".git-helpe-rs.toml")),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.YML")),
+            ConfigFormat::Yaml
+        );
+    }
+
+    #[test]
+    fn reports_line_and_column_of_parse_error() {
+        let error = ConfigFormat::Json
+            .parse("{\n  \"commit_template_variants\": {\"a\": \"{}\",}\n}")
+            .unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(42)));
+        assert_eq!(error.message, "trailing comma");
+
+        let error = ConfigFormat::Toml
+            .parse("[commit_template_variants]\na = \"{}\"\nb = {}\"\n")
+            .unwrap_err();
+        assert_eq!(error.line, Some(3));
+
+        let error = ConfigFormat::Yaml
+            .parse("commit_template_variants:\n  a: '{}'\n b: x\n")
+            .unwrap_err();
+        assert_eq!(error.line, Some(3));
+
+        assert!(ConfigFormat::Json.parse_config("[]").is_err());
+        assert_eq!(
+            ConfigFormat::Toml.parse_config("").unwrap(),
+            serde_json::json!({})
+        );
+    }
+
+    #[test]
+    fn writes_every_format_keeping_key_order() {
+        let value = json!({
+            "clipboard_commands": {"copy": "pbcopy", "paste": "pbpaste"},
+            "commit_template_variants": {"z": "{} z", "default": "[{ticket}] {}", "a": "{} a"},
+            "autocomplete_values": null,
+            "ticket_keys": ["PAY", "OPS"]
+        });
+        let expected = without_nulls(&value);
+
+        for format in [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml] {
+            let contents = format.serialize(&value).unwrap();
+            let parsed = format.parse(&contents).unwrap();
+            assert_eq!(parsed, expected);
+
+            let keys: Vec<&String> = parsed["commit_template_variants"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect();
+            assert_eq!(keys, vec!["z", "default", "a"]);
+        }
+    }
+}
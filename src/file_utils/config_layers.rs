@@ -0,0 +1,292 @@
+use std::{fmt, fs, io::ErrorKind, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use super::{config_format::ConfigFormat, config_migrations::migrate};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayerKind {
+    Default,
+    Global,
+    Repo,
+}
+
+/// Single source of config values, later layers override earlier ones key by key
+#[derive(Debug, Clone)]
+pub struct ConfigLayer {
+    pub kind: LayerKind,
+    pub path: Option<PathBuf>,
+    pub value: Value,
+}
+
+impl fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.kind {
+            LayerKind::Default => "default",
+            LayerKind::Global => "global",
+            LayerKind::Repo => "repo",
+        };
+        match &self.path {
+            Some(path) => write!(f, "{}: {}", name, path.display()),
+            None => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Values that are run as programs. Repo config comes with whatever is cloned,
+/// so it can't set them
+const COMMAND_KEYS: [(&str, &str); 2] = [
+    ("clipboard_commands", "copy"),
+    ("clipboard_commands", "paste"),
+];
+
+/// Removes command keys from value, returns paths of those that were set
+fn remove_command_keys(value: &mut Value) -> Vec<String> {
+    COMMAND_KEYS
+        .iter()
+        .filter(|(section, key)| {
+            value
+                .get_mut(*section)
+                .and_then(|section| section.as_object_mut())
+                .and_then(|section| section.remove(*key))
+                .is_some_and(|removed| !removed.is_null())
+        })
+        .map(|(section, key)| format!("{}.{}", section, key))
+        .collect()
+}
+
+impl ConfigLayer {
+    /// Missing file gives an empty layer, file that can't be parsed is an error,
+    /// older layouts are migrated in memory. Commands in repo config are ignored
+    pub fn read(kind: LayerKind, path: PathBuf) -> Result<ConfigLayer> {
+        let mut value = match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let value = ConfigFormat::from_path(&path)
+                    .parse_config(&contents)
+                    .map_err(|err| err.with_path(&path))?;
+                let (value, _) =
+                    migrate(value).map_err(|err| anyhow!("Config {}: {}", path.display(), err))?;
+                value
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Value::Object(Default::default()),
+            Err(err) => return Err(anyhow!("Couldn't read config {}: {}", path.display(), err)),
+        };
+        if kind == LayerKind::Repo {
+            for removed in remove_command_keys(&mut value) {
+                eprintln!(
+                    "Ignoring {} of repo config {}, commands are taken only from global config",
+                    removed,
+                    path.display()
+                );
+            }
+        }
+
+        Ok(ConfigLayer {
+            kind,
+            path: Some(path),
+            value,
+        })
+    }
+}
+
+/// Objects are merged key by key, nulls in overlay don't override anything
+pub fn merge_values(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (_, Value::Null) => {}
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(key) {
+                    Some(base_value) => merge_values(base_value, value),
+                    None => {
+                        base.insert(key.to_owned(), value.to_owned());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.to_owned(),
+    }
+}
+
+/// Entries of these sections are replaced as a whole, so template from repo
+/// config doesn't get description or args of global one with the same key
+const SECTIONS_WITH_WHOLE_ENTRIES: [&str; 2] =
+    ["commit_template_variants", "branch_template_variants"];
+
+pub fn merge_layers(layers: &[ConfigLayer]) -> Value {
+    let mut merged = Value::Object(Default::default());
+    for layer in layers {
+        for section in SECTIONS_WITH_WHOLE_ENTRIES {
+            let (Some(Value::Object(entries)), Some(Value::Object(overlay))) =
+                (merged.get_mut(section), layer.value.get(section))
+            else {
+                continue;
+            };
+            for (key, value) in overlay {
+                match entries.get_mut(key) {
+                    Some(entry) if !value.is_null() => *entry = value.to_owned(),
+                    _ => {}
+                }
+            }
+        }
+        merge_values(&mut merged, &layer.value);
+    }
+    merged
+}
+
+fn get_path<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |value, key| value.get(key))
+        .filter(|value| !value.is_null())
+}
+
+fn collect_leaves(value: &Value, path: Vec<String>, leaves: &mut Vec<(Vec<String>, Value)>) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let mut path = path.clone();
+                path.push(key.to_owned());
+                collect_leaves(value, path, leaves);
+            }
+        }
+        _ => leaves.push((path, value.to_owned())),
+    }
+}
+
+/// Every merged value with the layer it was taken from
+pub fn value_origins(layers: &[ConfigLayer]) -> Vec<(String, Value, &ConfigLayer)> {
+    let mut leaves = vec![];
+    collect_leaves(&merge_layers(layers), vec![], &mut leaves);
+
+    leaves
+        .into_iter()
+        .filter_map(|(path, value)| {
+            let layer = layers
+                .iter()
+                .rev()
+                .find(|layer| get_path(&layer.value, &path).is_some())?;
+            Some((path.join("."), value, layer))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layer(kind: LayerKind, value: Value) -> ConfigLayer {
+        ConfigLayer {
+            kind,
+            path: None,
+            value,
+        }
+    }
+
+    #[test]
+    fn merges_layers_key_by_key() {
+        let layers = vec![
+            layer(
+                LayerKind::Default,
+                json!({"clipboard_commands": {"copy": "pbcopy", "paste": "pbpaste"}, "ticket_keys": null}),
+            ),
+            layer(
+                LayerKind::Global,
+                json!({"clipboard_commands": {"copy": "xclip"}, "commit_template_variants": {"default": "{}", "sec": "{} - {}"}}),
+            ),
+            layer(
+                LayerKind::Repo,
+                json!({"commit_template_variants": {"default": "[{ticket}] {}"}, "ticket_keys": ["PAY"]}),
+            ),
+        ];
+
+        assert_eq!(
+            merge_layers(&layers),
+            json!({
+                "clipboard_commands": {"copy": "xclip", "paste": "pbpaste"},
+                "commit_template_variants": {"default": "[{ticket}] {}", "sec": "{} - {}"},
+                "ticket_keys": ["PAY"]
+            })
+        );
+    }
+
+    #[test]
+    fn replaces_templates_as_a_whole() {
+        let layers = vec![
+            layer(
+                LayerKind::Global,
+                json!({"commit_template_variants": {
+                    "default": {"template": "{}", "description": "Anything"},
+                    "fix": {"template": "fix: {}"}
+                }}),
+            ),
+            layer(
+                LayerKind::Repo,
+                json!({"commit_template_variants": {"default": {"template": "[{ticket}] {}"}}}),
+            ),
+        ];
+
+        let merged = merge_layers(&layers);
+        assert_eq!(
+            merged["commit_template_variants"],
+            json!({"default": {"template": "[{ticket}] {}"}, "fix": {"template": "fix: {}"}})
+        );
+        let keys: Vec<&String> = merged["commit_template_variants"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        assert_eq!(keys, vec!["default", "fix"]);
+    }
+
+    #[test]
+    fn ignores_commands_of_repo_config() {
+        let path = std::env::temp_dir().join(format!(
+            "git-helpe-rs-repo-config-{}.json",
+            std::process::id()
+        ));
+        fs::write(
+            &path,
+            r#"{"clipboard_commands": {"paste": "./pwn.sh"}, "ticket_keys": ["PAY"]}"#,
+        )
+        .unwrap();
+
+        let repo = ConfigLayer::read(LayerKind::Repo, path.clone()).unwrap();
+        let global = ConfigLayer::read(LayerKind::Global, path.clone()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(repo.value["clipboard_commands"], json!({}));
+        assert_eq!(repo.value["ticket_keys"], json!(["PAY"]));
+        assert_eq!(global.value["clipboard_commands"]["paste"], "./pwn.sh");
+    }
+
+    #[test]
+    fn finds_origin_of_each_value() {
+        let layers = vec![
+            layer(
+                LayerKind::Default,
+                json!({"clipboard_commands": {"copy": "pbcopy", "paste": "pbpaste"}}),
+            ),
+            layer(
+                LayerKind::Global,
+                json!({"clipboard_commands": {"copy": "xclip"}}),
+            ),
+            layer(
+                LayerKind::Repo,
+                json!({"clipboard_commands": {"copy": null}}),
+            ),
+        ];
+
+        let origins: Vec<(String, LayerKind)> = value_origins(&layers)
+            .into_iter()
+            .map(|(path, _, layer)| (path, layer.kind))
+            .collect();
+        assert_eq!(
+            origins,
+            vec![
+                ("clipboard_commands.copy".to_owned(), LayerKind::Global),
+                ("clipboard_commands.paste".to_owned(), LayerKind::Default),
+            ]
+        );
+    }
+}
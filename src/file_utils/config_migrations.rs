@@ -0,0 +1,179 @@
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+/// Version of config layout written by this build
+pub const CURRENT_SCHEMA_VERSION: u64 = 2;
+
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Upgrades config from `from` version to the next one
+pub struct Migration {
+    pub from: u64,
+    pub description: &'static str,
+    pub migrate: fn(&mut Map<String, Value>) -> Result<()>,
+}
+
+/// Config written before schema_version was introduced is version 1,
+/// every change of layout adds a migration from the previous version here
+pub const MIGRATIONS: [Migration; 1] = [Migration {
+    from: 1,
+    description: "store commit and branch templates as objects with `template` field",
+    migrate: templates_to_objects,
+}];
+
+/// `"default": "{}"` becomes `"default": {"template": "{}"}`
+fn templates_to_objects(config: &mut Map<String, Value>) -> Result<()> {
+    for section in ["commit_template_variants", "branch_template_variants"] {
+        let Some(Value::Object(variants)) = config.get_mut(section) else {
+            continue;
+        };
+        for variant in variants.values_mut() {
+            if let Value::String(template) = variant {
+                let template = Value::String(std::mem::take(template));
+                *variant = Value::Object(Map::from_iter([("template".to_owned(), template)]));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Missing version means config from before versioning
+pub fn schema_version(value: &Value) -> Result<u64> {
+    match value.get(SCHEMA_VERSION_KEY) {
+        None | Some(Value::Null) => Ok(1),
+        Some(version) => version
+            .as_u64()
+            .filter(|version| *version > 0)
+            .ok_or_else(|| {
+                anyhow!(
+                    "schema_version should be a positive number, got {}",
+                    version
+                )
+            }),
+    }
+}
+
+/// Runs every migration needed to get config to current version,
+/// returns descriptions of those that were applied
+pub fn migrate(value: Value) -> Result<(Value, Vec<&'static str>)> {
+    migrate_with(value, &MIGRATIONS, CURRENT_SCHEMA_VERSION)
+}
+
+fn migrate_with(
+    value: Value,
+    migrations: &[Migration],
+    current_version: u64,
+) -> Result<(Value, Vec<&'static str>)> {
+    let mut version = schema_version(&value)?;
+    if version > current_version {
+        return Err(anyhow!(
+            "Config has schema_version {}, but this git-helpe-rs knows only up to {} \n \
+            Update git-helpe-rs to use it",
+            version,
+            current_version
+        ));
+    }
+
+    let Value::Object(mut config) = value else {
+        return Err(anyhow!("Config should be a map of sections"));
+    };
+    let mut applied = vec![];
+    while version < current_version {
+        let migration = migrations
+            .iter()
+            .find(|migration| migration.from == version)
+            .ok_or_else(|| anyhow!("There is no migration from schema_version {}", version))?;
+        (migration.migrate)(&mut config)?;
+        applied.push(migration.description);
+        version += 1;
+    }
+
+    // Version goes first, so it's the first thing seen in the file
+    let mut migrated = Map::from_iter([(SCHEMA_VERSION_KEY.to_owned(), Value::from(version))]);
+    migrated.extend(
+        config
+            .into_iter()
+            .filter(|(key, _)| key != SCHEMA_VERSION_KEY),
+    );
+    Ok((Value::Object(migrated), applied))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rename_prefixes(config: &mut Map<String, Value>) -> Result<()> {
+        if let Some(prefixes) = config.remove("prefixes") {
+            config.insert("branch_prefix_variants".to_owned(), prefixes);
+        }
+        Ok(())
+    }
+
+    fn add_ticket_keys(config: &mut Map<String, Value>) -> Result<()> {
+        config.insert("ticket_keys".to_owned(), json!([]));
+        Ok(())
+    }
+
+    const TEST_MIGRATIONS: [Migration; 2] = [
+        Migration {
+            from: 1,
+            description: "rename prefixes",
+            migrate: rename_prefixes,
+        },
+        Migration {
+            from: 2,
+            description: "add ticket keys",
+            migrate: add_ticket_keys,
+        },
+    ];
+
+    #[test]
+    fn runs_migrations_from_config_version() {
+        let (migrated, applied) =
+            migrate_with(json!({"prefixes": {"f": "feature/"}}), &TEST_MIGRATIONS, 3).unwrap();
+        assert_eq!(
+            migrated,
+            json!({"schema_version": 3, "branch_prefix_variants": {"f": "feature/"}, "ticket_keys": []})
+        );
+        assert_eq!(applied, vec!["rename prefixes", "add ticket keys"]);
+
+        let (migrated, applied) = migrate_with(
+            json!({"schema_version": 2, "prefixes": {}}),
+            &TEST_MIGRATIONS,
+            3,
+        )
+        .unwrap();
+        assert_eq!(
+            migrated,
+            json!({"schema_version": 3, "prefixes": {}, "ticket_keys": []})
+        );
+        assert_eq!(applied, vec!["add ticket keys"]);
+    }
+
+    #[test]
+    fn refuses_config_from_newer_version() {
+        assert!(migrate_with(json!({"schema_version": 4}), &TEST_MIGRATIONS, 3).is_err());
+        assert!(migrate(json!({"schema_version": "v1"})).is_err());
+    }
+
+    #[test]
+    fn moves_templates_into_objects() {
+        let (migrated, applied) = migrate(json!({
+            "commit_template_variants": {"default": "{}", "sec": {"template": "{} - {}"}},
+            "branch_template_variants": {"default": "{b}-{}"},
+            "branch_prefix_variants": {"f": "feature/"}
+        }))
+        .unwrap();
+        assert_eq!(
+            migrated,
+            json!({
+                "schema_version": 2,
+                "commit_template_variants": {"default": {"template": "{}"}, "sec": {"template": "{} - {}"}},
+                "branch_template_variants": {"default": {"template": "{b}-{}"}},
+                "branch_prefix_variants": {"f": "feature/"}
+            })
+        );
+        assert_eq!(applied.len(), 1);
+    }
+}
@@ -0,0 +1,393 @@
+use serde_json::{Map, Value};
+
+use super::{config_format::ConfigFormat, config_layers::merge_values};
+
+/// What could be read from a broken config file
+#[derive(Debug, PartialEq)]
+pub struct Salvaged {
+    pub value: Value,
+    /// Keys, or lines when even the key couldn't be read, that were dropped
+    pub dropped: Vec<String>,
+}
+
+/// Reads every section (and inside a broken section every entry)
+/// that parses on its own, names of sections help to find where
+/// a broken JSON section ends
+pub fn salvage_sections(contents: &str, format: ConfigFormat, sections: &[String]) -> Salvaged {
+    let mut salvaged = Salvaged {
+        value: Value::Object(Map::new()),
+        dropped: vec![],
+    };
+    match format {
+        ConfigFormat::Json => {
+            let start = contents.find('{').map_or(contents.len(), |start| start + 1);
+            salvage_json_object(contents, start, "", sections, &mut salvaged);
+        }
+        ConfigFormat::Toml => salvage_toml(contents, &mut salvaged),
+        ConfigFormat::Yaml => salvage_yaml(contents, &mut salvaged),
+    }
+    salvaged
+}
+
+fn line_of(contents: &str, offset: usize) -> String {
+    format!("line {}", contents[..offset].matches('\n').count() + 1)
+}
+
+fn skip_whitespace(contents: &str, mut position: usize) -> usize {
+    while let Some(c) = contents[position..].chars().next() {
+        if !c.is_whitespace() {
+            break;
+        }
+        position += c.len_utf8();
+    }
+    position
+}
+
+/// Returns position right after JSON string starting at given position
+fn skip_json_string(contents: &str, position: usize) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in contents[position + 1..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(position + 1 + index + 1),
+            '\n' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn end_of_line(contents: &str, position: usize) -> usize {
+    contents[position..]
+        .find('\n')
+        .map_or(contents.len(), |end| position + end)
+}
+
+/// Returns position where value starting at given position ends,
+/// that is first `,` or closing bracket outside of nested values,
+/// unterminated string ends the value at the end of its line
+fn skip_json_value(contents: &str, mut position: usize) -> usize {
+    let mut depth = 0;
+    while let Some(c) = contents[position..].chars().next() {
+        match c {
+            '"' => match skip_json_string(contents, position) {
+                Some(end) => {
+                    position = end;
+                    continue;
+                }
+                None => return end_of_line(contents, position),
+            },
+            '{' | '[' => depth += 1,
+            '}' | ']' if depth == 0 => return position,
+            '}' | ']' => depth -= 1,
+            ',' if depth == 0 => return position,
+            _ => {}
+        }
+        position += c.len_utf8();
+    }
+    position
+}
+
+/// Position of the next `"section":` key, a broken value never goes past it
+fn next_section_key(contents: &str, start: usize, sections: &[String]) -> usize {
+    sections
+        .iter()
+        .filter_map(|section| {
+            let key = format!("\"{}\"", section);
+            contents[start..]
+                .match_indices(&key)
+                .map(|(index, _)| start + index)
+                .find(|&index| {
+                    let before = contents[..index].trim_end();
+                    let after = contents[index + key.len()..].trim_start();
+                    (before.ends_with(',') || before.ends_with('{')) && after.starts_with(':')
+                })
+        })
+        .min()
+        .unwrap_or(contents.len())
+}
+
+/// Returns position right after the object
+fn salvage_json_object(
+    contents: &str,
+    mut position: usize,
+    prefix: &str,
+    sections: &[String],
+    salvaged: &mut Salvaged,
+) -> usize {
+    loop {
+        position = skip_whitespace(contents, position);
+        match contents[position..].chars().next() {
+            None => return position,
+            Some('}') => return position + 1,
+            Some(',') => {
+                position += 1;
+                continue;
+            }
+            Some('"') => {}
+            Some(_) => {
+                // Something that isn't a key, skip until next entry
+                salvaged.dropped.push(line_of(contents, position));
+                position = skip_json_value(contents, position).max(position + 1);
+                continue;
+            }
+        }
+
+        let Some(key_end) = skip_json_string(contents, position) else {
+            salvaged.dropped.push(line_of(contents, position));
+            position = end_of_line(contents, position);
+            continue;
+        };
+        let key = serde_json::from_str::<String>(&contents[position..key_end])
+            .unwrap_or_else(|_| contents[position + 1..key_end - 1].to_owned());
+        if !prefix.is_empty() && sections.contains(&key) {
+            // Section name inside of section, the broken one wasn't closed
+            return position;
+        }
+        let path = format!("{}{}", prefix, key);
+
+        let value_start = skip_whitespace(contents, key_end);
+        if !contents[value_start..].starts_with(':') {
+            salvaged.dropped.push(path);
+            position = skip_json_value(contents, value_start);
+            continue;
+        }
+        let value_start = skip_whitespace(contents, value_start + 1);
+        let value_end = skip_json_value(contents, value_start).min(next_section_key(
+            contents,
+            value_start,
+            sections,
+        ));
+
+        position = match serde_json::from_str::<Value>(&contents[value_start..value_end]) {
+            Ok(value) => {
+                insert_at(&mut salvaged.value, &path, value);
+                value_end
+            }
+            Err(_) if contents[value_start..].starts_with('{') => {
+                insert_at(&mut salvaged.value, &path, Value::Object(Map::new()));
+                salvage_json_object(
+                    contents,
+                    value_start + 1,
+                    &format!("{}.", path),
+                    sections,
+                    salvaged,
+                )
+            }
+            Err(_) => {
+                salvaged.dropped.push(path);
+                value_end
+            }
+        };
+    }
+}
+
+fn insert_at(target: &mut Value, path: &str, value: Value) {
+    let nested = path.rsplit('.').fold(value, |value, key| {
+        Value::Object(Map::from_iter([(key.to_owned(), value)]))
+    });
+    merge_values(target, &nested);
+}
+
+fn parse_fragment(format: ConfigFormat, fragment: &str) -> Option<Value> {
+    format
+        .parse_config(fragment)
+        .ok()
+        .filter(|value| value.as_object().is_some_and(|object| !object.is_empty()))
+}
+
+/// Sections start at `[table]` headers, keys before the first one are read line by line
+fn salvage_toml(contents: &str, salvaged: &mut Salvaged) {
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index].trim();
+        if !line.starts_with('[') {
+            if !line.is_empty() && !line.starts_with('#') {
+                match parse_fragment(ConfigFormat::Toml, line) {
+                    Some(value) => merge_values(&mut salvaged.value, &value),
+                    None => salvaged.dropped.push(format!("line {}", index + 1)),
+                }
+            }
+            index += 1;
+            continue;
+        }
+
+        let header_index = index;
+        index += 1;
+        while index < lines.len() && !lines[index].trim().starts_with('[') {
+            index += 1;
+        }
+        let section = lines[header_index..index].join("\n");
+        if let Some(value) = parse_fragment(ConfigFormat::Toml, &section) {
+            merge_values(&mut salvaged.value, &value);
+            continue;
+        }
+
+        for (entry_index, entry) in lines.iter().enumerate().take(index).skip(header_index + 1) {
+            let entry = entry.trim();
+            if entry.is_empty() || entry.starts_with('#') {
+                continue;
+            }
+            match parse_fragment(ConfigFormat::Toml, &format!("{}\n{}", line, entry)) {
+                Some(value) => merge_values(&mut salvaged.value, &value),
+                None => salvaged.dropped.push(format!("line {}", entry_index + 1)),
+            }
+        }
+    }
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_yaml_content(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+/// Sections start at lines without indentation, in a broken section
+/// each entry with indentation of the first one is read on its own
+fn salvage_yaml(contents: &str, salvaged: &mut Salvaged) {
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut index = 0;
+    while index < lines.len() {
+        if !is_yaml_content(lines[index]) || lines[index] == "---" {
+            index += 1;
+            continue;
+        }
+
+        let header_index = index;
+        index += 1;
+        while index < lines.len()
+            && (!is_yaml_content(lines[index]) || indentation(lines[index]) > 0)
+        {
+            index += 1;
+        }
+        if let Some(value) =
+            parse_fragment(ConfigFormat::Yaml, &lines[header_index..index].join("\n"))
+        {
+            merge_values(&mut salvaged.value, &value);
+            continue;
+        }
+
+        let children: Vec<usize> = (header_index + 1..index)
+            .filter(|&child| is_yaml_content(lines[child]))
+            .collect();
+        let Some(child_indentation) = children.first().map(|&child| indentation(lines[child]))
+        else {
+            salvaged.dropped.push(format!("line {}", header_index + 1));
+            continue;
+        };
+        let entry_starts: Vec<usize> = children
+            .into_iter()
+            .filter(|&child| indentation(lines[child]) <= child_indentation)
+            .collect();
+        for (position, &entry_start) in entry_starts.iter().enumerate() {
+            let entry_end = entry_starts.get(position + 1).copied().unwrap_or(index);
+            let entry = format!(
+                "{}\n{}",
+                lines[header_index],
+                lines[entry_start..entry_end].join("\n")
+            );
+            match parse_fragment(ConfigFormat::Yaml, &entry) {
+                Some(value) => merge_values(&mut salvaged.value, &value),
+                None => salvaged.dropped.push(format!("line {}", entry_start + 1)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sections() -> Vec<String> {
+        [
+            "clipboard_commands",
+            "commit_template_variants",
+            "ticket_keys",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    #[test]
+    fn salvages_valid_json_sections_and_entries() {
+        let contents = r#"{
+  "clipboard_commands": {"copy": "xclip", "paste": "xclip -o"},
+  "commit_template_variants": {
+    "default": "[{ticket}] {}",
+    "broken": "{} ,
+    "sec": "{} - {}"
+  },
+  "ticket_keys": ["PAY",],
+  "autocomplete_values": ["web""#;
+
+        let salvaged = salvage_sections(contents, ConfigFormat::Json, &sections());
+        assert_eq!(
+            salvaged.value,
+            json!({
+                "clipboard_commands": {"copy": "xclip", "paste": "xclip -o"},
+                    "commit_template_variants": {"default": "[{ticket}] {}", "sec": "{} - {}"}
+            })
+        );
+        assert_eq!(
+            salvaged.dropped,
+            vec![
+                "commit_template_variants.broken",
+                "ticket_keys",
+                "autocomplete_values"
+            ]
+        );
+    }
+
+    #[test]
+    fn ends_unclosed_json_section_at_next_section() {
+        let contents =
+            r#"{"commit_template_variants":{"default":"{}","bad":"{},"ticket_keys":["PAY"]}"#;
+
+        let salvaged = salvage_sections(contents, ConfigFormat::Json, &sections());
+        assert_eq!(
+            salvaged.value,
+            json!({"commit_template_variants": {"default": "{}"}, "ticket_keys": ["PAY"]})
+        );
+        assert_eq!(salvaged.dropped, vec!["commit_template_variants.bad"]);
+    }
+
+    #[test]
+    fn salvages_valid_toml_sections_and_entries() {
+        let contents = "ticket_keys = [\"PAY\"]\nbranch_pattern = \"(?P<b>\n\n\
+            [clipboard_commands]\ncopy = \"xclip\"\npaste = \"xclip -o\"\n\n\
+            [commit_template_variants]\ndefault = \"{}\"\nbroken = {} x\"\n";
+
+        let salvaged = salvage_sections(contents, ConfigFormat::Toml, &sections());
+        assert_eq!(
+            salvaged.value,
+            json!({
+                "ticket_keys": ["PAY"],
+                "clipboard_commands": {"copy": "xclip", "paste": "xclip -o"},
+                "commit_template_variants": {"default": "{}"}
+            })
+        );
+        assert_eq!(salvaged.dropped, vec!["line 2", "line 10"]);
+    }
+
+    #[test]
+    fn salvages_valid_yaml_sections_and_entries() {
+        let contents = "clipboard_commands:\n  copy: xclip\n  paste: xclip -o\n\
+            commit_template_variants:\n  default: '{}'\n  broken: '{} x\n  sec: '{} - {}'\n";
+
+        let salvaged = salvage_sections(contents, ConfigFormat::Yaml, &sections());
+        assert_eq!(
+            salvaged.value,
+            json!({
+                "clipboard_commands": {"copy": "xclip", "paste": "xclip -o"},
+                "commit_template_variants": {"default": "{}", "sec": "{} - {}"}
+            })
+        );
+        assert_eq!(salvaged.dropped, vec!["line 6"]);
+    }
+}
@@ -1 +1,6 @@
 pub mod config_file;
+pub mod config_format;
+pub mod config_layers;
+pub mod config_migrations;
+pub mod config_repair;
+pub mod text_diff;
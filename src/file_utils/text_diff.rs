@@ -0,0 +1,50 @@
+/// Unified-like diff of two texts, unchanged lines start with two spaces,
+/// removed with `- ` and added with `+ `
+pub fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j] is length of longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_removed_and_added_lines() {
+        let old = "{\n  \"a\": \"{}\",\n  \"b\": 1\n}";
+        let new = "{\n  \"schema_version\": 1,\n  \"a\": \"{}\",\n  \"b\": 2\n}";
+        assert_eq!(
+            line_diff(old, new),
+            "  {\n+   \"schema_version\": 1,\n    \"a\": \"{}\",\n-   \"b\": 1\n+   \"b\": 2\n  }"
+        );
+    }
+}
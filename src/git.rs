@@ -0,0 +1,77 @@
+use std::process::Command;
+
+use anyhow::{anyhow, Result};
+
+/// Runs git with given args and returns trimmed stdout
+pub fn git_output(args: &[&str]) -> Result<String> {
+    let output = Command::new("git").args(args).output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: \n {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+pub fn config_value(key: &str) -> Option<String> {
+    git_output(&["config", key])
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+/// symbolic-ref works also before first commit, rev-parse covers detached HEAD
+pub fn current_branch() -> Result<String> {
+    git_output(&["symbolic-ref", "--short", "HEAD"])
+        .or_else(|_| git_output(&["rev-parse", "--abbrev-ref", "HEAD"]))
+}
+
+pub fn short_sha() -> Result<String> {
+    git_output(&["rev-parse", "--short", "HEAD"])
+}
+
+pub fn repo_name() -> Result<String> {
+    let top_level = git_output(&["rev-parse", "--show-toplevel"])?;
+    top_level
+        .rsplit(['/', '\\'])
+        .next()
+        .map(|name| name.to_owned())
+        .ok_or_else(|| anyhow!("Couldn't read repository name from {}", top_level))
+}
+
+/// Returns `owner/name` of origin remote
+pub fn remote_name() -> Result<String> {
+    let url = git_output(&["remote", "get-url", "origin"])?;
+    parse_remote_url(&url).ok_or_else(|| anyhow!("Couldn't read owner/name from remote {}", url))
+}
+
+fn parse_remote_url(url: &str) -> Option<String> {
+    let url = url.trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let mut segments = url.rsplit(['/', ':']);
+    let name = segments.next().filter(|name| !name.is_empty())?;
+    let owner = segments.next().filter(|owner| !owner.is_empty())?;
+
+    Some(format!("{}/{}", owner, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_owner_and_name_from_remote_url() {
+        assert_eq!(
+            parse_remote_url("git@github.com:Zolwiastyl/git-helpe-rs.git"),
+            Some("Zolwiastyl/git-helpe-rs".to_owned())
+        );
+        assert_eq!(
+            parse_remote_url("https://github.com/Zolwiastyl/git-helpe-rs"),
+            Some("Zolwiastyl/git-helpe-rs".to_owned())
+        );
+        assert_eq!(parse_remote_url("origin"), None);
+    }
+}
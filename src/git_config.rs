@@ -1,38 +1,115 @@
-use std::{collections::HashMap, fs, path::PathBuf};
+use std::path::{Path, PathBuf};
 
 use crate::{
-    cli::{CommitOperationArguments, SetClipboardCommands, SetFormat, UseTemplate},
-    file_utils::config_file::get_path_to_config,
+    branch_name::{compile_branch_pattern, BranchNameMode},
+    cli::{
+        CommitOperationArguments, ConvertConfig, MigrateConfig, RepairConfig, SetClipboardCommands,
+        SetFormat, TemplateMetadata, UseTemplate,
+    },
+    conventional::{validate_conventional_types, DEFAULT_SUBJECT_MAX_LENGTH, DEFAULT_TYPES},
+    existing_branch::ExistingBranchAction,
+    file_utils::{
+        config_file::{
+            convert_config_file, get_path_to_config, get_path_to_repo_config, write_atomically,
+            ConfigLock,
+        },
+        config_format::{ConfigFormat, ConfigParseError},
+        config_layers::{merge_layers, value_origins, ConfigLayer, LayerKind},
+        config_migrations::{migrate, schema_version, CURRENT_SCHEMA_VERSION},
+        config_repair::{salvage_sections, Salvaged},
+        text_diff::line_diff,
+    },
+    template::{Template, TemplateVariant},
+    ticket::ticket_patterns,
 };
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
+use indexmap::IndexMap;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GitConfig {
+    /// Values merged from default, global and repo layers
     pub data: Data,
+    /// Global config file, all changes are written there
     config_path: PathBuf,
+    #[serde(skip)]
+    layers: Vec<ConfigLayer>,
 }
 
-type Variants = HashMap<String, String>;
+/// Keeps the order in which variants were added, also in written file
+type Variants = IndexMap<String, String>;
+type TemplateVariants = IndexMap<String, TemplateVariant>;
 
-#[derive(Debug, Serialize, Deserialize)]
+const DEFAULT_COMMIT_BODY_WIDTH: usize = 72;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClipboardCommands {
     pub copy: String,
     pub paste: String,
 }
-#[derive(Debug, Serialize, Deserialize)]
+/// People to pair with and those paired with now
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pairing {
+    /// Short handle to `Name <email>`
+    pub roster: Variants,
+    /// Handles added as Co-authored-by to each commit
+    pub active: Vec<String>,
+}
+
+/// Rules `cc` and its message check follow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConventionalCommits {
+    /// Types allowed in `type(scope): description`
+    pub types: Vec<String>,
+    /// Longest allowed first line of message, 0 turns the check off
+    pub subject_max_length: usize,
+}
+
+impl Default for ConventionalCommits {
+    fn default() -> Self {
+        ConventionalCommits {
+            types: DEFAULT_TYPES
+                .iter()
+                .map(|type_| type_.to_string())
+                .collect(),
+            subject_max_length: DEFAULT_SUBJECT_MAX_LENGTH,
+        }
+    }
+}
+
+/// Missing fields are taken from defaults, so older files stay readable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Data {
+    pub schema_version: u64,
     pub clipboard_commands: ClipboardCommands,
-    pub commit_template_variants: Variants,
-    pub branch_template_variants: Variants,
+    pub commit_template_variants: TemplateVariants,
+    pub branch_template_variants: TemplateVariants,
     pub branch_prefix_variants: Variants,
     pub autocomplete_values: Option<Vec<String>>,
+    /// Regex with named groups matched against current branch name
+    pub branch_pattern: Option<String>,
+    /// Project keys like PAY or regexes used to find ticket for `{ticket}`
+    pub ticket_keys: Option<Vec<String>>,
+    /// Whether ticket is looked for in clipboard when branch has none
+    pub ticket_from_clipboard: bool,
+    /// Body of commit message is wrapped to it, 0 turns wrapping off
+    pub commit_body_width: usize,
+    pub pair: Pairing,
+    pub conventional: ConventionalCommits,
+    /// Whether branch names git would reject are fixed or refused
+    pub branch_name_mode: BranchNameMode,
+    /// What bt and bp do when branch already exists, templates can override it
+    pub existing_branch: ExistingBranchAction,
 }
 
 pub struct Templates {
-    pub commit_template_variants: Variants,
-    pub branch_template_variants: Variants,
+    pub commit_template_variants: TemplateVariants,
+    pub branch_template_variants: TemplateVariants,
 }
 
 pub enum BranchOrCommitAction {
@@ -40,75 +117,149 @@ pub enum BranchOrCommitAction {
     BranchFromTemplate(UseTemplate),
 }
 
-impl Data {
+impl Default for Data {
     fn default() -> Self {
         Data {
+            schema_version: CURRENT_SCHEMA_VERSION,
             clipboard_commands: ClipboardCommands {
                 copy: "pbcopy".to_string(),
                 paste: "pbpaste".to_string(),
             },
-            commit_template_variants: HashMap::new(),
-            branch_template_variants: HashMap::new(),
-            branch_prefix_variants: HashMap::new(),
+            commit_template_variants: IndexMap::new(),
+            branch_template_variants: IndexMap::new(),
+            branch_prefix_variants: IndexMap::new(),
             autocomplete_values: None,
+            branch_pattern: None,
+            ticket_keys: None,
+            ticket_from_clipboard: false,
+            commit_body_width: DEFAULT_COMMIT_BODY_WIDTH,
+            pair: Pairing::default(),
+            conventional: ConventionalCommits::default(),
+            branch_name_mode: BranchNameMode::default(),
+            existing_branch: ExistingBranchAction::default(),
         }
     }
 }
 
 impl GitConfig {
-    fn default_config() -> Self {
-        return GitConfig {
-            data: Data::default(),
-            config_path: get_path_to_config(None).to_path_buf(),
-        };
+    fn default_layer() -> ConfigLayer {
+        ConfigLayer {
+            kind: LayerKind::Default,
+            path: None,
+            value: serde_json::to_value(Data::default()).unwrap(),
+        }
+    }
+
+    fn from_layers(layers: Vec<ConfigLayer>, config_path: PathBuf) -> Result<Self> {
+        let data = Self::data_from_layers(&layers)?;
+
+        Ok(GitConfig {
+            data,
+            config_path,
+            layers,
+        })
+    }
+
+    /// Tells which layer has value of wrong type, as merged value doesn't know it
+    fn data_from_layers(layers: &[ConfigLayer]) -> Result<Data> {
+        serde_json::from_value(merge_layers(layers)).map_err(|err| {
+            let faulty_layer = (1..=layers.len())
+                .find(|count| {
+                    serde_json::from_value::<Data>(merge_layers(&layers[..*count])).is_err()
+                })
+                .map(|count| &layers[count - 1]);
+            match faulty_layer {
+                Some(layer) => anyhow!("Invalid config ({}): {}", layer, err),
+                None => anyhow!("Invalid config: {}", err),
+            }
+        })
     }
 
     pub fn new_config(
         clipboard_commands: ClipboardCommands,
         branch_prefix_variants: Variants,
-        branch_format_variants: Variants,
-        commit_format_variants: Variants,
+        branch_format_variants: TemplateVariants,
+        commit_format_variants: TemplateVariants,
         config_path: Option<PathBuf>,
     ) -> Self {
-        return GitConfig {
-            data: Data {
-                clipboard_commands,
-                branch_template_variants: branch_format_variants,
-                commit_template_variants: commit_format_variants,
-                branch_prefix_variants,
-                autocomplete_values: None,
-            },
-            config_path: if let Some(config_path) = config_path {
-                config_path
-            } else {
-                get_path_to_config(None).to_path_buf()
-            },
+        let data = Data {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            clipboard_commands,
+            branch_template_variants: branch_format_variants,
+            commit_template_variants: commit_format_variants,
+            branch_prefix_variants,
+            autocomplete_values: None,
+            branch_pattern: None,
+            ticket_keys: None,
+            ticket_from_clipboard: false,
+            commit_body_width: DEFAULT_COMMIT_BODY_WIDTH,
+            pair: Pairing::default(),
+            conventional: ConventionalCommits::default(),
+            branch_name_mode: BranchNameMode::default(),
+            existing_branch: ExistingBranchAction::default(),
+        };
+        let config_path = if let Some(config_path) = config_path {
+            config_path
+        } else {
+            get_path_to_config(None).to_path_buf()
         };
-    }
 
-    pub fn from_file(path_to_file: PathBuf) -> Self {
-        if fs::metadata(&path_to_file).is_ok() {
-            let contents = fs::read_to_string(&path_to_file);
-            let contents = contents.unwrap_or(
-                "{\
-                    \"clipboard_commands\": {}, \
-                    \"branch_template_variants\": {}, \
-                    \"commit_template_variants\": {}, \
-                    \"branch_prefix_variants\": {}
-                }"
-                .to_string(),
-            );
+        let global_layer = ConfigLayer {
+            kind: LayerKind::Global,
+            path: Some(config_path.clone()),
+            value: serde_json::to_value(&data).unwrap(),
+        };
 
-            let data = serde_json::from_str(&contents);
-            let data: Data = data.unwrap_or(Data::default());
+        Self::from_layers(vec![Self::default_layer(), global_layer], config_path).unwrap()
+    }
 
-            GitConfig {
-                data: data,
-                config_path: path_to_file,
-            }
-        } else {
-            Self::default_config()
+    /// Reads global config from given path and merges repo config over it,
+    /// fails when any of them is broken, so it's never overwritten with defaults
+    pub fn from_file(path_to_file: PathBuf) -> Result<Self> {
+        let mut layers = vec![
+            Self::default_layer(),
+            ConfigLayer::read(LayerKind::Global, path_to_file.clone())?,
+        ];
+        if let Some(repo_config_path) = get_path_to_repo_config() {
+            layers.push(ConfigLayer::read(LayerKind::Repo, repo_config_path)?);
         }
+
+        Self::from_layers(layers, path_to_file)
+    }
+
+    /// Whether layer of given kind sets the section, or given key of it
+    pub(crate) fn is_defined_in(&self, kind: LayerKind, section: &str, key: Option<&str>) -> bool {
+        self.layers
+            .iter()
+            .filter(|layer| layer.kind == kind)
+            .filter_map(|layer| layer.value.get(section))
+            .filter(|value| !value.is_null())
+            .any(|value| key.is_none_or(|key| value.get(key).is_some()))
+    }
+
+    fn global_layer_index(&self) -> usize {
+        self.layers
+            .iter()
+            .position(|layer| layer.kind == LayerKind::Global)
+            .unwrap()
+    }
+
+    /// Changes only values of global config, so values from repo config
+    /// never leak into global file. Global config is read again under lock,
+    /// so changes made meanwhile by other process aren't lost
+    pub(crate) fn update<F: FnOnce(&mut Data) -> Result<()>>(&mut self, change: F) -> Result<()> {
+        let _lock = ConfigLock::acquire(&self.config_path)?;
+        let global_layer_index = self.global_layer_index();
+        self.layers[global_layer_index] =
+            ConfigLayer::read(LayerKind::Global, self.config_path.clone())?;
+        let mut global_data = Self::data_from_layers(&self.layers[..=global_layer_index])?;
+
+        change(&mut global_data)?;
+
+        self.save_to_file(&global_data)?;
+        self.layers[global_layer_index].value = serde_json::to_value(&global_data)?;
+        self.data = Self::data_from_layers(&self.layers)?;
+        Ok(())
     }
 
     pub fn validate_against_interpolation_regex<'a>(
@@ -119,39 +270,80 @@ impl GitConfig {
         if interpolation_regex.is_match(string_to_interpolate) {
             return Ok(string_to_interpolate);
         };
-        return Err(Error::msg(format!(
+        Err(Error::msg(format!(
             "There was no interpolation signature: {{}} introduced in {name_of_field_to_check}"
-        )));
+        )))
+    }
+
+    /// Template given again keeps description, tags and args of places it
+    /// still has, unless new ones are given
+    fn set_template_variant(variants: &mut TemplateVariants, arg: SetFormat) -> Result<()> {
+        let TemplateMetadata {
+            description,
+            tags,
+            prompts,
+            examples,
+            validations,
+            body,
+            trailers,
+            existing_branch,
+        } = arg.metadata;
+        let mut variant = variants.get(&arg.key).cloned().unwrap_or_default();
+        variant.template = arg.value;
+        // Args of places that are gone from the template aren't kept
+        let arg_keys = TemplateVariant::arg_keys(&variant.parse()?);
+        variant.args.retain(|key, _| arg_keys.contains(key));
+        if description.is_some() {
+            variant.description = description.filter(|description| !description.is_empty());
+        }
+        if let Some(tags) = tags {
+            variant.tags = tags;
+        }
+        let args = &mut variant.args;
+        for (name, prompt) in prompts {
+            args.entry(name).or_default().prompt = Some(prompt);
+        }
+        for (name, example) in examples {
+            args.entry(name).or_default().example = Some(example);
+        }
+        for (name, validation) in validations {
+            args.entry(name).or_default().validation = Some(validation);
+        }
+        if let Some(body) = body {
+            variant.body = Some(body).filter(|body| !body.is_empty());
+        }
+        for (key, value) in trailers {
+            match value.is_empty() {
+                true => variant.trailers.shift_remove(&key),
+                false => variant.trailers.insert(key, value),
+            };
+        }
+        if let Some(existing_branch) = existing_branch {
+            variant.existing_branch = existing_branch;
+        }
+        variant.validate_args()?;
+
+        variants.insert(arg.key, variant);
+        Ok(())
     }
 
     pub fn set_branch_template_variant(&mut self, arg: SetFormat) -> Result<()> {
-        let result = Self::validate_against_interpolation_regex(&arg.value, "branch_template");
-        match result {
-            Err(e) => panic!("{}", e),
-            Ok(_) => self
-                .data
-                .branch_template_variants
-                .insert(arg.key, arg.value),
-        };
-        self.save_to_file()
+        Template::parse(&arg.value)?;
+        Self::validate_against_interpolation_regex(&arg.value, "branch_template")?;
+        self.update(|data| Self::set_template_variant(&mut data.branch_template_variants, arg))
     }
 
     pub fn set_commit_template_variant(&mut self, arg: SetFormat) -> Result<()> {
-        let result = Self::validate_against_interpolation_regex(&arg.value, "commit_template");
-        match result {
-            Err(e) => panic!("{}", e),
-            Ok(_) => {
-                self.data
-                    .commit_template_variants
-                    .insert(arg.key, arg.value);
-            }
-        };
-        self.save_to_file()
+        Template::parse(&arg.value)?;
+        Self::validate_against_interpolation_regex(&arg.value, "commit_template")?;
+        self.update(|data| Self::set_template_variant(&mut data.commit_template_variants, arg))
     }
 
     pub fn set_branch_prefix_variant(&mut self, key: String, value: String) -> Result<()> {
-        self.data.branch_prefix_variants.insert(key, value);
-        self.save_to_file()
+        self.update(|data| {
+            data.branch_prefix_variants.insert(key, value);
+            Ok(())
+        })
     }
 
     pub fn set_clipboard_command(&mut self, args: SetClipboardCommands) -> Result<()> {
@@ -160,39 +352,307 @@ impl GitConfig {
             paste: args.paste,
         };
 
-        self.data.clipboard_commands = new_clipboard_commands;
-        self.save_to_file()
+        self.update(|data| {
+            data.clipboard_commands = new_clipboard_commands;
+            Ok(())
+        })
+    }
+
+    pub fn set_autocomplete_values(&mut self, values: Vec<String>) -> Result<()> {
+        self.update(|data| {
+            data.autocomplete_values = Some(values);
+            Ok(())
+        })
+    }
+
+    pub fn set_commit_body_width(&mut self, width: usize) -> Result<()> {
+        self.update(|data| {
+            data.commit_body_width = width;
+            Ok(())
+        })
+    }
+
+    /// No types bring back the default ones
+    pub fn set_conventional_types(&mut self, types: Vec<String>) -> Result<()> {
+        let types = if types.is_empty() {
+            ConventionalCommits::default().types
+        } else {
+            validate_conventional_types(&types)?;
+            types
+        };
+        self.update(|data| {
+            data.conventional.types = types;
+            Ok(())
+        })
+    }
+
+    pub fn set_subject_max_length(&mut self, length: usize) -> Result<()> {
+        self.update(|data| {
+            data.conventional.subject_max_length = length;
+            Ok(())
+        })
+    }
+
+    pub fn set_branch_name_mode(&mut self, mode: BranchNameMode) -> Result<()> {
+        self.update(|data| {
+            data.branch_name_mode = mode;
+            Ok(())
+        })
+    }
+
+    pub fn set_existing_branch(&mut self, action: ExistingBranchAction) -> Result<()> {
+        self.update(|data| {
+            data.existing_branch = action;
+            Ok(())
+        })
+    }
+
+    pub fn set_branch_pattern(&mut self, pattern: String) -> Result<()> {
+        let branch_pattern = if pattern.is_empty() {
+            None
+        } else {
+            compile_branch_pattern(&pattern)?;
+            Some(pattern)
+        };
+        self.update(|data| {
+            data.branch_pattern = branch_pattern;
+            Ok(())
+        })
+    }
+
+    pub fn set_ticket_keys(&mut self, ticket_keys: Vec<String>) -> Result<()> {
+        ticket_patterns(&ticket_keys)?;
+        self.update(|data| {
+            data.ticket_keys = if ticket_keys.is_empty() {
+                None
+            } else {
+                Some(ticket_keys)
+            };
+            Ok(())
+        })
+    }
+
+    pub fn set_ticket_from_clipboard(&mut self, from_clipboard: bool) -> Result<()> {
+        self.update(|data| {
+            data.ticket_from_clipboard = from_clipboard;
+            Ok(())
+        })
+    }
+
+    fn save_to_file(&self, data: &Data) -> Result<()> {
+        let contents =
+            ConfigFormat::from_path(&self.config_path).serialize(&serde_json::to_value(data)?)?;
+        write_atomically(&self.config_path, &contents)
     }
 
-    pub fn delete_branch_prefix_variant(&mut self, key: String) -> Result<()> {
-        let old_val = self.data.branch_prefix_variants.remove(&key);
+    fn layer_path(&self, repo: bool) -> Result<&PathBuf> {
+        let kind = if repo {
+            LayerKind::Repo
+        } else {
+            LayerKind::Global
+        };
+        self.layers
+            .iter()
+            .find(|layer| layer.kind == kind)
+            .and_then(|layer| layer.path.as_ref())
+            .ok_or_else(|| anyhow!("There is no repo config in current repository"))
+    }
+
+    pub fn convert(&self, args: ConvertConfig) -> Result<()> {
+        let format = ConfigFormat::from_name(&args.format)
+            .ok_or_else(|| anyhow!("Unknown config format {}", args.format))?;
+        let path = self.layer_path(args.repo)?;
+
+        let _lock = ConfigLock::acquire(path)?;
+        let (new_path, old_path) = convert_config_file(path, format)?;
         println!(
-            "Removed {} : {} from config ",
-            key,
-            old_val.unwrap_or(String::from("None"))
+            "Converted {} to {}, old file was moved to {}",
+            path.display(),
+            new_path.display(),
+            old_path.display()
         );
-        self.save_to_file()
+        Ok(())
+    }
+
+    /// Rewrites config file in current schema, dry run only shows the diff
+    pub fn migrate(&self, args: MigrateConfig) -> Result<()> {
+        let path = self.layer_path(args.repo)?;
+        let _lock = ConfigLock::acquire(path)?;
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| anyhow!("Couldn't read config {}: {}", path.display(), err))?;
+        let format = ConfigFormat::from_path(path);
+
+        let value = format.parse_config(&contents)?;
+        let version = schema_version(&value)?;
+        let (migrated, applied) = migrate(value)?;
+        let migrated_contents = format.serialize(&migrated)?;
+
+        if migrated_contents == contents {
+            println!(
+                "Config {} is already at schema_version {}",
+                path.display(),
+                CURRENT_SCHEMA_VERSION
+            );
+            return Ok(());
+        }
+
+        if version == CURRENT_SCHEMA_VERSION {
+            println!(
+                "Rewriting {} with schema_version {}",
+                path.display(),
+                version
+            );
+        } else {
+            println!(
+                "Migrating {} from schema_version {} to {}",
+                path.display(),
+                version,
+                CURRENT_SCHEMA_VERSION
+            );
+        }
+        for description in applied {
+            println!(" - {}", description);
+        }
+        println!("{}", line_diff(&contents, &migrated_contents));
+
+        if args.dry_run {
+            return Ok(());
+        }
+        write_atomically(path, &migrated_contents)?;
+        println!("Config {} was migrated", path.display());
+        Ok(())
+    }
+
+    fn is_valid_section(key: &str, value: &Value) -> bool {
+        let section = ConfigLayer {
+            kind: LayerKind::Global,
+            path: None,
+            value: Value::Object(Map::from_iter([(key.to_owned(), value.to_owned())])),
+        };
+        Self::data_from_layers(&[Self::default_layer(), section]).is_ok()
+    }
+
+    /// Drops sections, or entries of sections, which have values of wrong type
+    fn drop_invalid_sections(salvaged: &mut Salvaged) {
+        let Value::Object(sections) = &mut salvaged.value else {
+            return;
+        };
+        sections.retain(|key, value| {
+            if Self::is_valid_section(key, value) {
+                return true;
+            }
+            if let Value::Object(entries) = value {
+                entries.retain(|entry_key, entry_value| {
+                    let entry = Value::Object(Map::from_iter([(
+                        entry_key.to_owned(),
+                        entry_value.to_owned(),
+                    )]));
+                    let is_valid = Self::is_valid_section(key, &entry);
+                    if !is_valid {
+                        salvaged.dropped.push(format!("{}.{}", key, entry_key));
+                    }
+                    is_valid
+                });
+                return true;
+            }
+            salvaged.dropped.push(key.to_owned());
+            false
+        });
     }
 
-    fn save_to_file(&self) -> Result<()> {
-        if let Some(dir) = self.config_path.parent() {
-            if !std::fs::metadata(&dir).is_ok() {
-                std::fs::create_dir_all(dir)?;
+    /// Writes back valid part of config and moves the original aside
+    pub fn repair(path_to_config: &Path, args: &RepairConfig) -> Result<()> {
+        let path = if args.repo {
+            get_path_to_repo_config()
+                .ok_or_else(|| anyhow!("There is no repo config in current repository"))?
+        } else {
+            path_to_config.to_path_buf()
+        };
+        let _lock = ConfigLock::acquire(&path)?;
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|err| anyhow!("Couldn't read config {}: {}", path.display(), err))?;
+        let format = ConfigFormat::from_path(&path);
+
+        let (mut salvaged, parse_error) = match format.parse_config(&contents) {
+            Ok(value) => (
+                Salvaged {
+                    value,
+                    dropped: vec![],
+                },
+                None,
+            ),
+            Err(err) => {
+                let sections: Vec<String> = Self::default_layer()
+                    .value
+                    .as_object()
+                    .unwrap()
+                    .keys()
+                    .cloned()
+                    .collect();
+                (salvage_sections(&contents, format, &sections), Some(err))
             }
         };
+        Self::drop_invalid_sections(&mut salvaged);
 
-        let contents = serde_json::to_string(&self.data)?;
-        std::fs::write(&self.config_path, contents)?;
-        return Ok(());
+        if parse_error.is_none() && salvaged.dropped.is_empty() {
+            println!("Config {} is valid, nothing to repair", path.display());
+            return Ok(());
+        }
+        if let Some(ConfigParseError {
+            message,
+            line: Some(line),
+            column: Some(column),
+            ..
+        }) = parse_error
+        {
+            println!(
+                "Parse error at line {}, column {}: {}",
+                line, column, message
+            );
+        }
+
+        let mut backup_path = path.clone().into_os_string();
+        backup_path.push(chrono::Local::now().format(".%Y%m%d%H%M%S.bak").to_string());
+        std::fs::copy(&path, &backup_path)?;
+        write_atomically(&path, &format.serialize(&salvaged.value)?)?;
+
+        let kept: Vec<&String> = salvaged.value.as_object().unwrap().keys().collect();
+        println!(
+            "Repaired {} \n kept: {:?} \n dropped: {:?} \n original was kept as {}",
+            path.display(),
+            kept,
+            salvaged.dropped,
+            PathBuf::from(backup_path).display()
+        );
+        Ok(())
+    }
+
+    /// Each value with the layer it comes from
+    pub fn display_config_origins(&self) -> String {
+        value_origins(&self.layers)
+            .into_iter()
+            .map(|(path, value, layer)| format!("{} = {}    ({})", path, value, layer))
+            .collect::<Vec<String>>()
+            .join("\n")
     }
 
     pub fn display_config(&self) -> Result<String> {
         let clipboard_command = &self.data.clipboard_commands;
         let copy = &clipboard_command.copy;
         let paste = &clipboard_command.paste;
-        let branch = self.data.branch_template_variants.to_owned();
-        let commit = self.data.commit_template_variants.to_owned();
+        // Metadata of templates is shown by `commit-template list`
+        let templates = |variants: &TemplateVariants| -> IndexMap<String, String> {
+            variants
+                .iter()
+                .map(|(key, variant)| (key.to_owned(), variant.template.to_owned()))
+                .collect()
+        };
+        let branch = templates(&self.data.branch_template_variants);
+        let commit = templates(&self.data.commit_template_variants);
         let prefixes = self.data.branch_prefix_variants.to_owned();
+        let autocomplete = self.data.autocomplete_values.to_owned().unwrap_or_default();
+        let branch_pattern = self.data.branch_pattern.to_owned().unwrap_or_default();
+        let ticket_keys = self.data.ticket_keys.to_owned().unwrap_or_default();
 
         Ok(format!(
             "
@@ -203,8 +663,30 @@ impl GitConfig {
         branch formats: {:?} 
         commit formats: {:?} 
         branch prefixes: {:?} 
+        autocomplete values: {:?} 
+        branch pattern: {:?} 
+        ticket keys: {:?} 
+        ticket from clipboard: {} 
+        commit body width: {} 
+        conventional types: {:?} 
+        subject max length: {} 
+        branch name mode: {:?} 
+        existing branch: {} 
         ",
-            *copy, *paste, branch, commit, prefixes
+            *copy,
+            *paste,
+            branch,
+            commit,
+            prefixes,
+            autocomplete,
+            branch_pattern,
+            ticket_keys,
+            self.data.ticket_from_clipboard,
+            self.data.commit_body_width,
+            self.data.conventional.types,
+            self.data.conventional.subject_max_length,
+            self.data.branch_name_mode,
+            self.data.existing_branch
         ))
     }
 }
@@ -0,0 +1,345 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+
+use crate::{
+    cli::{HookCommand, InstallHooks},
+    commit::add_branch_values,
+    commit_message::CommitMessage,
+    conventional::validate_message,
+    editor::{comment_char, strip_message},
+    git,
+    git_config::GitConfig,
+    pair::coauthor_trailers,
+    template::{InterpolationValues, Node, Template, TemplateVariant},
+};
+
+/// Only hooks with it are overwritten by install-hooks without --force
+const HOOK_MARKER: &str = "# Installed by git-helpe-rs";
+
+const HOOKS: [&str; 2] = ["commit-msg", "prepare-commit-msg"];
+
+/// Messages written by git itself, e.g. for merges or `git commit --fixup`.
+/// Merge forms are the ones git and pull requests generate, not any "Merge ..." subject
+fn is_written_by_git(subject: &str) -> bool {
+    [
+        "Merge branch '",
+        "Merge branches '",
+        "Merge remote-tracking branch '",
+        "Merge tag '",
+        "Merge commit '",
+        "Merge pull request #",
+        "Revert \"",
+        "fixup! ",
+        "squash! ",
+        "amend! ",
+    ]
+    .iter()
+    .any(|prefix| subject.starts_with(prefix))
+}
+
+/// Subject has to match subject of any commit template, body is free
+pub fn check_against_templates(
+    message: &str,
+    variants: &IndexMap<String, TemplateVariant>,
+) -> Result<()> {
+    let subject = message.lines().next().unwrap_or_default().trim();
+    if variants.is_empty() {
+        return Ok(());
+    }
+    for variant in variants.values() {
+        if variant.subject_matcher()?.is_match(subject) {
+            return Ok(());
+        }
+    }
+
+    let templates: Vec<String> = variants
+        .iter()
+        .map(|(key, variant)| format!("  {}: {}", key, variant.template))
+        .collect();
+    Err(anyhow!(
+        "Commit message `{}` doesn't match any commit template:\n{}\n\
+        Format it with git-helpe-rs c, or skip this check with git commit --no-verify",
+        subject,
+        templates.join("\n")
+    ))
+}
+
+fn commit_msg(file: &Path, conventional: bool, config: &GitConfig) -> Result<()> {
+    let text = fs::read_to_string(file)
+        .map_err(|err| anyhow!("Couldn't read message file {}: {}", file.display(), err))?;
+    let message = strip_message(&text, comment_char());
+    // Empty message is rejected by git on its own
+    let subject = message.lines().next().unwrap_or_default();
+    if message.is_empty() || is_written_by_git(subject) {
+        return Ok(());
+    }
+
+    if conventional {
+        validate_message(&message, &config.data.conventional).map_err(|err| {
+            anyhow!(
+                "Commit message doesn't follow Conventional Commits:{}\n\
+                Format it with git-helpe-rs cc, or skip this check with git commit --no-verify",
+                err
+            )
+        })
+    } else {
+        check_against_templates(&message, &config.data.commit_template_variants)
+    }
+}
+
+/// Places without value are rendered empty, to be filled in editor.
+/// Defaults are given explicitly, as empty value would replace them
+fn fill_missing_with_empty(template: &Template, values: &mut InterpolationValues) {
+    let mut autocomplete_count = 0;
+    for (node, _) in template.placeholders() {
+        match node {
+            Node::Positional(placeholder) => values
+                .positional
+                .push(placeholder.default.clone().unwrap_or_default()),
+            Node::Named(placeholder) => {
+                values
+                    .named
+                    .entry(placeholder.name.to_owned())
+                    .or_insert_with(|| placeholder.default.clone().unwrap_or_default());
+            }
+            Node::Context(variable, placeholder)
+                if values.context_value(variable, &placeholder.name).is_none() =>
+            {
+                values
+                    .named
+                    .insert(placeholder.name.to_owned(), String::new());
+            }
+            Node::BranchNumber(_) if values.branch_number.is_none() => {
+                values.branch_number = Some(String::new())
+            }
+            Node::Autocomplete(_) => autocomplete_count += 1,
+            _ => {}
+        }
+    }
+    let autocomplete = values.autocomplete.get_or_insert_with(Vec::new);
+    while autocomplete.len() < autocomplete_count {
+        autocomplete.push(String::new());
+    }
+}
+
+/// Default commit template filled with values taken like `c -b` does, but
+/// without looking into clipboard. None when there is no default template
+fn prefilled_message(config: &GitConfig) -> Result<Option<String>> {
+    let Some(variant) = config.data.commit_template_variants.get("default") else {
+        return Ok(None);
+    };
+    let template = variant.parse()?;
+    let mut values: InterpolationValues = vec![].into();
+
+    // Branch without number still gives values of branch pattern
+    if add_branch_values(&template, &mut values, template.has_branch_number(), config).is_err() {
+        let _ = add_branch_values(&template, &mut values, false, config);
+    }
+    if template.autocomplete_count() > 0 {
+        values.autocomplete = config.data.autocomplete_values.clone();
+    }
+    values.context = template.resolve_context_without_clipboard(&config.data);
+    fill_missing_with_empty(&template, &mut values);
+
+    let mut message = CommitMessage::from_rendered(
+        variant,
+        &template.render(&values)?,
+        config.data.commit_body_width,
+    );
+    message.add_trailers(coauthor_trailers(&config.data));
+    Ok(Some(message.to_string()))
+}
+
+/// Message is there already for every source git passes: -m or -F, -t or
+/// commit.template, merge, squash and -c, -C or --amend
+fn prepare_commit_msg(file: &Path, source: Option<&str>, path_to_config: &Path) -> Result<()> {
+    if source.is_some() {
+        return Ok(());
+    }
+    // Commit isn't stopped only because message couldn't be pre-filled,
+    // not even when config is broken
+    let config = match GitConfig::from_file(path_to_config.to_path_buf()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Couldn't pre-fill commit message: {:?}", err);
+            return Ok(());
+        }
+    };
+    let message = match prefilled_message(&config) {
+        Ok(Some(message)) => message,
+        Ok(None) => return Ok(()),
+        Err(err) => {
+            eprintln!("Couldn't pre-fill commit message: {}", err);
+            return Ok(());
+        }
+    };
+    let text = fs::read_to_string(file)
+        .map_err(|err| anyhow!("Couldn't read message file {}: {}", file.display(), err))?;
+    fs::write(file, format!("{}\n{}", message, text))?;
+    Ok(())
+}
+
+/// Entry points for hooks, they fail with non-zero exit code so git stops.
+/// Config is read by each hook, as prepare-commit-msg mustn't fail on it
+pub fn run_hook(command: HookCommand, path_to_config: &Path) -> Result<()> {
+    match command {
+        HookCommand::CommitMsg { file, conventional } => commit_msg(
+            &file,
+            conventional,
+            &GitConfig::from_file(path_to_config.to_path_buf())?,
+        ),
+        HookCommand::PrepareCommitMsg { file, source } => {
+            prepare_commit_msg(&file, source.as_deref(), path_to_config)
+        }
+    }
+}
+
+/// Hook runs the binary that installed it, so it works also where it isn't
+/// in PATH, e.g. in IDEs
+fn hook_script(executable: &Path, hook: &str, conventional: bool) -> String {
+    let executable = executable.to_string_lossy().replace('\'', "'\\''");
+    let conventional = if conventional && hook == "commit-msg" {
+        " --conventional"
+    } else {
+        ""
+    };
+    format!(
+        "#!/bin/sh\n\
+        {}, reinstall with git-helpe-rs install-hooks\n\
+        exec '{}' hook {}{} \"$@\"\n",
+        HOOK_MARKER, executable, hook, conventional
+    )
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Hooks go to core.hooksPath when it's set, .git/hooks otherwise
+pub fn install_hooks(args: InstallHooks) -> Result<()> {
+    let hooks_dir = PathBuf::from(git::git_output(&["rev-parse", "--git-path", "hooks"])?);
+    let paths: Vec<(&str, PathBuf)> = HOOKS
+        .iter()
+        .map(|hook| (*hook, hooks_dir.join(hook)))
+        .collect();
+
+    // Checked before writing any, so hooks aren't installed halfway
+    if !args.force {
+        for (_, path) in &paths {
+            if let Ok(existing) = fs::read_to_string(path) {
+                if !existing.contains(HOOK_MARKER) {
+                    return Err(anyhow!(
+                        "{} already exists and wasn't installed by git-helpe-rs \n \
+                        run with --force to overwrite it",
+                        path.display()
+                    ));
+                }
+            }
+        }
+    }
+
+    fs::create_dir_all(&hooks_dir)?;
+    let executable = std::env::current_exe()?;
+    for (hook, path) in paths {
+        fs::write(&path, hook_script(&executable, hook, args.conventional))?;
+        make_executable(&path)?;
+        println!("Installed {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_subject_matching_any_template() {
+        let variants: IndexMap<String, TemplateVariant> =
+            serde_json::from_value(serde_json::json!({
+                "default": {
+                    "template": "[{ticket}] {}",
+                    "args": {"ticket": {"validation": "[A-Z]+-\\d+"}}
+                },
+                "chore": "chore: {}"
+            }))
+            .unwrap();
+
+        check_against_templates("[PAY-1] fix login\n\nBody\n", &variants).unwrap();
+        check_against_templates("chore: bump deps\n", &variants).unwrap();
+        assert_eq!(
+            check_against_templates("[pay] fix login\n", &variants)
+                .unwrap_err()
+                .to_string(),
+            "Commit message `[pay] fix login` doesn't match any commit template:\n  \
+            default: [{ticket}] {}\n  \
+            chore: chore: {}\n\
+            Format it with git-helpe-rs c, or skip this check with git commit --no-verify"
+        );
+    }
+
+    #[test]
+    fn skips_only_subjects_generated_by_git() {
+        assert!(is_written_by_git("Merge branch 'main' into feat/x"));
+        assert!(is_written_by_git(
+            "Merge remote-tracking branch 'origin/main'"
+        ));
+        assert!(is_written_by_git("Merge pull request #12 from org/feat"));
+        assert!(is_written_by_git("Merge tag 'v1.2.0'"));
+        assert!(is_written_by_git("fixup! [PAY-1] fix login"));
+        assert!(!is_written_by_git("Merge sort for reports"));
+        assert!(!is_written_by_git("Merged login forms"));
+    }
+
+    #[test]
+    fn leaves_places_without_value_empty() {
+        let template = Template::parse("{type=chore}: [{ticket}] {} {?({scope})}{[]}").unwrap();
+        let mut values: InterpolationValues = vec![].into();
+        fill_missing_with_empty(&template, &mut values);
+        assert_eq!(template.render(&values).unwrap(), "chore: []  ");
+    }
+
+    #[test]
+    fn prepare_commit_msg_passes_with_broken_config() {
+        let dir = std::env::temp_dir().join(format!(
+            "git-helpe-rs-broken-hook-config-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("config.json");
+        let message = dir.join("COMMIT_EDITMSG");
+        fs::write(&config, r#"{"commit_template_variants": {"#).unwrap();
+        fs::write(&message, "\n# Please enter the commit message\n").unwrap();
+
+        let plain_commit = prepare_commit_msg(&message, None, &config);
+        let amend = prepare_commit_msg(&message, Some("commit"), &config);
+        let text = fs::read_to_string(&message).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(plain_commit.is_ok());
+        assert!(amend.is_ok());
+        assert_eq!(text, "\n# Please enter the commit message\n");
+    }
+
+    #[test]
+    fn hook_runs_binary_that_installed_it() {
+        assert_eq!(
+            hook_script(Path::new("/opt/it's/git-helpe-rs"), "commit-msg", true),
+            "#!/bin/sh\n\
+            # Installed by git-helpe-rs, reinstall with git-helpe-rs install-hooks\n\
+            exec '/opt/it'\\''s/git-helpe-rs' hook commit-msg --conventional \"$@\"\n"
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+
+use crate::template::{arg_label, InterpolationValues, Node, Template, TemplateVariant};
+
+/// Asks for template and its values, reads answers line by line
+pub struct Prompter<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+/// Place that has no value yet, keyed like template args:
+/// by name, or by position for `{}`
+struct Place {
+    key: String,
+    required: bool,
+    default: Option<String>,
+}
+
+/// Missing values are asked for without -i only when there is someone to ask
+pub fn stdin_is_terminal() -> bool {
+    io::stdin().is_terminal()
+}
+
+impl Prompter<io::StdinLock<'static>, io::Stdout> {
+    pub fn stdio() -> Self {
+        Prompter::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Prompter { input, output }
+    }
+
+    fn ask(&mut self, question: &str) -> Result<String> {
+        write!(self.output, "{}", question)?;
+        self.output.flush()?;
+        let mut answer = String::new();
+        if self.input.read_line(&mut answer)? == 0 {
+            return Err(anyhow!("No answer given, aborting"));
+        }
+        Ok(answer.trim_end_matches(['\n', '\r']).to_owned())
+    }
+
+    /// Template can be picked by its number or key, `default` is picked on enter
+    pub fn pick_key(
+        &mut self,
+        title: &str,
+        variants: &IndexMap<String, TemplateVariant>,
+    ) -> Result<String> {
+        if variants.is_empty() {
+            return Err(anyhow!(
+                "There are no {} to pick from",
+                title.to_lowercase()
+            ));
+        }
+        writeln!(self.output, "{}:", title)?;
+        for (index, (key, variant)) in variants.iter().enumerate() {
+            write!(
+                self.output,
+                "  {}) {}: {}",
+                index + 1,
+                key,
+                variant.template
+            )?;
+            if let Some(description) = &variant.description {
+                write!(self.output, " - {}", description)?;
+            }
+            writeln!(self.output)?;
+        }
+
+        let default = variants.get_index_of("default").unwrap_or(0);
+        loop {
+            let answer = self.ask(&format!("Pick template [{}]: ", default + 1))?;
+            let answer = answer.trim();
+            if answer.is_empty() {
+                return Ok(variants.get_index(default).unwrap().0.to_owned());
+            }
+            let picked = answer
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| variants.get_index(number.checked_sub(1)?))
+                .or_else(|| variants.get_key_value(answer))
+                .map(|(key, _)| key.to_owned());
+            match picked {
+                Some(key) => return Ok(key),
+                None => writeln!(
+                    self.output,
+                    "There is no template `{}`, pick its number or key",
+                    answer
+                )?,
+            }
+        }
+    }
+
+    /// Numbered menu, first option is picked on enter
+    pub fn pick_option(&mut self, title: &str, options: &[String]) -> Result<usize> {
+        writeln!(self.output, "{}:", title)?;
+        for (index, option) in options.iter().enumerate() {
+            writeln!(self.output, "  {}) {}", index + 1, option)?;
+        }
+        loop {
+            let answer = self.ask("Pick [1]: ")?;
+            let answer = answer.trim();
+            if answer.is_empty() {
+                return Ok(0);
+            }
+            match answer.parse::<usize>() {
+                Ok(number) if (1..=options.len()).contains(&number) => return Ok(number - 1),
+                _ => writeln!(self.output, "Pick number from 1 to {}", options.len())?,
+            }
+        }
+    }
+
+    /// Asks for every place that has no value yet, showing how the result
+    /// looks like after each answer
+    pub fn fill_values(
+        &mut self,
+        variant: &TemplateVariant,
+        template: &Template,
+        values: &mut InterpolationValues,
+        render: &dyn Fn(&InterpolationValues) -> Result<String>,
+    ) -> Result<()> {
+        let places = places_to_ask(template, values);
+        if places.is_empty() {
+            return Ok(());
+        }
+        if let Some(description) = &variant.description {
+            writeln!(self.output, "{}", description)?;
+        }
+        self.show_preview(values, &places, render)?;
+
+        for (index, place) in places.iter().enumerate() {
+            let answer = self.ask_for_place(variant, place)?;
+            if !answer.is_empty() {
+                match place.key.parse::<usize>() {
+                    Ok(_) => values.positional.push(answer),
+                    Err(_) => {
+                        values.named.insert(place.key.to_owned(), answer);
+                    }
+                }
+            }
+            self.show_preview(values, &places[index + 1..], render)?;
+        }
+        Ok(())
+    }
+
+    fn ask_for_place(&mut self, variant: &TemplateVariant, place: &Place) -> Result<String> {
+        let mut question = arg_label(&place.key);
+        if let Some(arg) = variant.args.get(&place.key) {
+            if let Some(prompt) = &arg.prompt {
+                question.push_str(&format!(" {}", prompt));
+            }
+            if let Some(example) = &arg.example {
+                question.push_str(&format!(" (e.g. {})", example));
+            }
+        }
+        match (&place.default, place.required) {
+            (Some(default), _) => question.push_str(&format!(" [{}]", default)),
+            (None, false) => question.push_str(" (optional)"),
+            (None, true) => {}
+        }
+        question.push_str(": ");
+
+        loop {
+            let answer = self.ask(&question)?;
+            if answer.is_empty() {
+                if place.required {
+                    writeln!(self.output, "Value is required")?;
+                    continue;
+                }
+                return Ok(answer);
+            }
+            match variant.validate_value(&place.key, &answer) {
+                Ok(()) => return Ok(answer),
+                Err(err) => writeln!(self.output, "{}", err)?,
+            }
+        }
+    }
+
+    /// Places still to be asked for are shown as `<name>`
+    fn show_preview(
+        &mut self,
+        values: &InterpolationValues,
+        pending: &[Place],
+        render: &dyn Fn(&InterpolationValues) -> Result<String>,
+    ) -> Result<()> {
+        let mut preview = values.clone();
+        for place in pending {
+            let marker = format!("<{}>", place.key);
+            match place.key.parse::<usize>() {
+                Ok(_) => preview.positional.push(marker),
+                Err(_) => {
+                    preview.named.insert(place.key.to_owned(), marker);
+                }
+            }
+        }
+        if let Ok(rendered) = render(&preview) {
+            writeln!(self.output, "Preview: {}", rendered)?;
+        }
+        Ok(())
+    }
+
+    /// Enter means yes
+    pub fn confirm(&mut self, question: &str) -> Result<bool> {
+        loop {
+            let answer = self.ask(&format!("{} [Y/n]: ", question))?;
+            match answer.trim().to_lowercase().as_str() {
+                "" | "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Places in order of the template: names not given, built-ins that couldn't
+/// be resolved and `{}` after those already given
+fn places_to_ask(template: &Template, values: &InterpolationValues) -> Vec<Place> {
+    let required_names = template.required_names();
+    let mut places: Vec<Place> = vec![];
+    let mut position = 0;
+
+    for (node, required) in template.placeholders() {
+        let place = match node {
+            Node::Named(placeholder) if !values.named.contains_key(&placeholder.name) => Place {
+                key: placeholder.name.to_owned(),
+                required: required_names.contains(&placeholder.name.as_str()),
+                default: placeholder.default.clone(),
+            },
+            Node::Context(variable, placeholder)
+                if required && values.context_value(variable, &placeholder.name).is_none() =>
+            {
+                Place {
+                    key: placeholder.name.to_owned(),
+                    required,
+                    default: None,
+                }
+            }
+            Node::Positional(placeholder) => {
+                position += 1;
+                if position <= values.positional.len() {
+                    continue;
+                }
+                Place {
+                    key: position.to_string(),
+                    required,
+                    default: placeholder.default.clone(),
+                }
+            }
+            _ => continue,
+        };
+        if !places.iter().any(|asked| asked.key == place.key) {
+            places.push(place);
+        }
+    }
+    places
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn prompter(input: &str) -> Prompter<Cursor<Vec<u8>>, Vec<u8>> {
+        Prompter::new(Cursor::new(input.as_bytes().to_vec()), vec![])
+    }
+
+    fn output(prompter: Prompter<Cursor<Vec<u8>>, Vec<u8>>) -> String {
+        String::from_utf8(prompter.output).unwrap()
+    }
+
+    #[test]
+    fn picks_template_by_number_or_key() {
+        let variants = IndexMap::from_iter([
+            ("fix".to_owned(), TemplateVariant::from("fix: {}")),
+            ("default".to_owned(), TemplateVariant::from("{}")),
+        ]);
+
+        let mut picking = prompter("\n");
+        assert_eq!(
+            picking.pick_key("Commit templates", &variants).unwrap(),
+            "default"
+        );
+        assert!(
+            output(picking).contains("  1) fix: fix: {}\n  2) default: {}\nPick template [2]: ")
+        );
+
+        let mut picking = prompter("3\nfix\n");
+        assert_eq!(
+            picking.pick_key("Commit templates", &variants).unwrap(),
+            "fix"
+        );
+        assert!(output(picking).contains("There is no template `3`"));
+    }
+
+    #[test]
+    fn asks_for_missing_places_until_valid() {
+        let variant: TemplateVariant = serde_json::from_value(serde_json::json!({
+            "template": "{type=chore}: [{ticket}] {}",
+            "args": {"ticket": {"prompt": "Ticket", "validation": "[A-Z]+-\\d+"}}
+        }))
+        .unwrap();
+        let template = variant.parse().unwrap();
+        let mut values: InterpolationValues = vec![].into();
+
+        let mut prompter = prompter("\nPAY\nPAY-1\n\nfix login\n");
+        prompter
+            .fill_values(&variant, &template, &mut values, &|values| {
+                template.render(values)
+            })
+            .unwrap();
+
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "chore: [PAY-1] fix login"
+        );
+        let output = output(prompter);
+        assert!(output.starts_with("Preview: <type>: [<ticket>] <1>\n{type} [chore]: "));
+        assert!(output.contains("Value `PAY` of {ticket} doesn't match"));
+        assert!(output.contains("{} #1: Value is required\n{} #1: "));
+        assert!(output.ends_with("Preview: chore: [PAY-1] fix login\n"));
+    }
+
+    #[test]
+    fn picks_option_by_number() {
+        let options = vec!["switch".to_owned(), "abort".to_owned()];
+        assert_eq!(prompter("\n").pick_option("Branch", &options).unwrap(), 0);
+        let mut picking = prompter("3\n2\n");
+        assert_eq!(picking.pick_option("Branch", &options).unwrap(), 1);
+        assert!(output(picking).contains("Pick number from 1 to 2"));
+    }
+
+    #[test]
+    fn confirms_on_enter_and_fails_without_input() {
+        assert!(prompter("\n").confirm("Run?").unwrap());
+        assert!(!prompter("maybe\nn\n").confirm("Run?").unwrap());
+        assert!(prompter("").confirm("Run?").is_err());
+    }
+}
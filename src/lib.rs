@@ -1,8 +1,20 @@
 pub mod autocompletion;
 pub mod branch;
+pub mod branch_name;
 pub mod cli;
+pub mod clipboard;
 pub mod commit;
+pub mod commit_message;
+pub mod config_sections;
+pub mod conventional;
+pub mod editor;
+pub mod existing_branch;
 pub mod file_utils;
+pub mod git;
 pub mod git_config;
+pub mod hooks;
+pub mod interactive;
+pub mod pair;
 pub mod run_mode;
 pub mod template;
+pub mod ticket;
@@ -0,0 +1,203 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::{
+    cli::PairCommand,
+    git_config::{Data, GitConfig, Pairing},
+};
+
+pub const COAUTHOR_TRAILER: &str = "Co-authored-by";
+
+/// Roster entries of people paired with now
+pub fn active_coauthors(data: &Data) -> Vec<String> {
+    data.pair
+        .active
+        .iter()
+        .filter_map(|handle| data.pair.roster.get(handle))
+        .cloned()
+        .collect()
+}
+
+/// Trailers added to each commit while pairing
+pub fn coauthor_trailers(data: &Data) -> Vec<(String, String)> {
+    active_coauthors(data)
+        .into_iter()
+        .map(|coauthor| (COAUTHOR_TRAILER.to_owned(), coauthor))
+        .collect()
+}
+
+/// GitHub recognizes co-authors only in `Name <email>` form
+fn validate_coauthor(coauthor: &str) -> Result<()> {
+    let coauthor_regex = Regex::new(r"^[^<>\s][^<>]*\s<[^<>\s]+@[^<>\s]+>$").unwrap();
+    if coauthor_regex.is_match(coauthor) {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "Co-author should look like `Name <email>`, got `{}`",
+        coauthor
+    ))
+}
+
+fn unknown_handle_error(handle: &str, pairing: &Pairing) -> anyhow::Error {
+    if pairing.roster.is_empty() {
+        return anyhow!(
+            "There is no `{}` in pair roster, add people with pair add",
+            handle
+        );
+    }
+    let available: Vec<&str> = pairing.roster.keys().map(|key| key.as_str()).collect();
+    anyhow!(
+        "There is no `{}` in pair roster, available: {}",
+        handle,
+        available.join(", ")
+    )
+}
+
+/// Roster with `*` in front of people paired with now
+fn list_pairing(pairing: &Pairing) -> String {
+    if pairing.roster.is_empty() {
+        return "Pair roster is empty, add people with pair add".to_owned();
+    }
+    pairing
+        .roster
+        .iter()
+        .map(|(handle, coauthor)| {
+            let marker = if pairing.active.contains(handle) {
+                "*"
+            } else {
+                " "
+            };
+            format!("{} {}: {}", marker, handle, coauthor)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Roster in merged config is used to check handles, so people from repo config
+/// can be paired with too
+fn change_pairing(pairing: &mut Pairing, merged: &Pairing, command: &PairCommand) -> Result<()> {
+    match command {
+        PairCommand::List => {}
+        PairCommand::Add { handle, coauthor } => {
+            validate_coauthor(coauthor)?;
+            pairing
+                .roster
+                .insert(handle.to_owned(), coauthor.to_owned());
+        }
+        PairCommand::Remove(handle) => {
+            if pairing.roster.shift_remove(handle).is_none() {
+                return Err(match merged.roster.contains_key(handle) {
+                    true => anyhow!("`{}` comes from repo config, change it there", handle),
+                    false => unknown_handle_error(handle, merged),
+                });
+            }
+            pairing.active.retain(|active| active != handle);
+        }
+        PairCommand::Start(handles) => {
+            if let Some(unknown) = handles
+                .iter()
+                .find(|handle| !merged.roster.contains_key(*handle))
+            {
+                return Err(unknown_handle_error(unknown, merged));
+            }
+            pairing.active.clear();
+            for handle in handles {
+                if !pairing.active.contains(handle) {
+                    pairing.active.push(handle.to_owned());
+                }
+            }
+        }
+        PairCommand::End(handles) if handles.is_empty() => pairing.active.clear(),
+        PairCommand::End(handles) => {
+            if let Some(not_active) = handles
+                .iter()
+                .find(|handle| !pairing.active.contains(handle))
+            {
+                return Err(anyhow!("You aren't pairing with `{}`", not_active));
+            }
+            pairing.active.retain(|active| !handles.contains(active));
+        }
+    }
+    Ok(())
+}
+
+impl GitConfig {
+    pub fn run_pair_command(&mut self, command: PairCommand) -> Result<()> {
+        if !matches!(command, PairCommand::List) {
+            let merged = self.data.pair.clone();
+            self.update(|data| change_pairing(&mut data.pair, &merged, &command))?;
+        }
+
+        match command {
+            PairCommand::Start(_) | PairCommand::End(_) => {
+                let coauthors = active_coauthors(&self.data);
+                if coauthors.is_empty() {
+                    println!("Not pairing with anyone");
+                } else {
+                    println!("Pairing with: {}", coauthors.join(", "));
+                }
+            }
+            _ => println!("{}", list_pairing(&self.data.pair)),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairing() -> Pairing {
+        Pairing {
+            roster: [
+                ("ann".to_owned(), "Ann Lee <ann@example.com>".to_owned()),
+                ("bob".to_owned(), "Bob Ray <bob@example.com>".to_owned()),
+            ]
+            .into_iter()
+            .collect(),
+            active: vec![],
+        }
+    }
+
+    #[test]
+    fn starts_and_ends_pairing() {
+        let mut pairing = pairing();
+        let start = PairCommand::Start(vec!["bob".to_owned(), "ann".to_owned()]);
+        change_pairing(&mut pairing, &self::pairing(), &start).unwrap();
+        assert_eq!(pairing.active, vec!["bob", "ann"]);
+
+        let end = PairCommand::End(vec!["bob".to_owned()]);
+        change_pairing(&mut pairing, &self::pairing(), &end).unwrap();
+        assert_eq!(pairing.active, vec!["ann"]);
+        assert!(change_pairing(&mut pairing, &self::pairing(), &end).is_err());
+
+        let start = PairCommand::Start(vec!["eve".to_owned()]);
+        assert_eq!(
+            change_pairing(&mut pairing, &self::pairing(), &start)
+                .unwrap_err()
+                .to_string(),
+            "There is no `eve` in pair roster, available: ann, bob"
+        );
+    }
+
+    #[test]
+    fn removing_from_roster_ends_pairing() {
+        let mut pairing = pairing();
+        pairing.active = vec!["ann".to_owned(), "bob".to_owned()];
+        change_pairing(
+            &mut pairing,
+            &self::pairing(),
+            &PairCommand::Remove("ann".to_owned()),
+        )
+        .unwrap();
+        assert_eq!(pairing.active, vec!["bob"]);
+        assert!(!pairing.roster.contains_key("ann"));
+    }
+
+    #[test]
+    fn accepts_only_name_with_email() {
+        assert!(validate_coauthor("Ann Lee <ann@example.com>").is_ok());
+        assert!(validate_coauthor("ann@example.com").is_err());
+        assert!(validate_coauthor("Ann <ann>").is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use std::process::{Command, Stdio};
 
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
 
 use crate::{cli::DryRunAndCopyFlag, git_config::GitConfig};
 
@@ -27,24 +27,73 @@ pub fn get_run_mode_from_options(flags: DryRunAndCopyFlag) -> RunMode {
     }
 }
 
+/// Single quotes arg unless shell would take it as it is
+fn quote_arg(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c));
+    if is_plain {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Command as it can be pasted to shell, shown for what is going to run
+pub fn command_line(program: &str, args: &[String]) -> String {
+    std::iter::once(program.to_owned())
+        .chain(args.iter().map(|arg| quote_arg(arg)))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Runs command and returns its stdout, failure carries exit status and stderr
+pub fn run_command(program: &str, args: &[String]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| anyhow!("Couldn't run {}: {}", program, err))?;
+
+    if !output.status.success() {
+        // git commit tells about nothing to commit on stdout
+        let reason = if output.stderr.is_empty() {
+            &output.stdout
+        } else {
+            &output.stderr
+        };
+        return Err(anyhow!(
+            "{} failed with {}: \n {}",
+            command_line(program, args),
+            output.status,
+            String::from_utf8_lossy(reason).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 pub fn run_copy(config: &GitConfig, value_to_copy: String) -> Result<(), Error> {
     let copy_command = config.data.clipboard_commands.copy.to_string();
 
-    let echo = Command::new("echo")
+    let mut echo = Command::new("echo")
         .arg(value_to_copy)
         .stdout(Stdio::piped())
         .spawn()
         .unwrap();
 
     Command::new(copy_command)
-        .stdin(Stdio::from(echo.stdout.unwrap()))
+        .stdin(Stdio::from(echo.stdout.take().unwrap()))
         .output()
         .unwrap();
 
+    echo.wait()?;
+
     Ok(())
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
 
@@ -94,4 +143,30 @@ mod tests {
             _ => assert!(false),
         }
     }
+
+    #[test]
+    fn fails_with_status_and_stderr_of_command() {
+        let args = vec!["-c".to_owned(), "echo broken >&2; exit 3".to_owned()];
+        let error = run_command("sh", &args).unwrap_err().to_string();
+        assert!(error.contains("exit status: 3"), "{}", error);
+        assert!(error.ends_with("broken"), "{}", error);
+
+        let error = run_command("no-such-program-here", &[]).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Couldn't run no-such-program-here"));
+    }
+
+    #[test]
+    fn quotes_args_of_command_line() {
+        let args = vec![
+            "commit".to_owned(),
+            "-m".to_owned(),
+            "fix: don't crash".to_owned(),
+        ];
+        assert_eq!(
+            command_line("git", &args),
+            "git commit -m 'fix: don'\\''t crash'"
+        );
+    }
 }
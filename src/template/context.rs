@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use chrono::format::{Item, StrftimeItems};
+
+use crate::{
+    git,
+    git_config::Data,
+    pair::active_coauthors,
+    ticket::{find_current_ticket, Ticket},
+};
+
+use super::{Node, Template};
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Variables that are filled from outside of the arguments, e.g. `{user.name}`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ContextVariable {
+    /// `{date}` or `{date:%d.%m.%Y}`
+    Date(Option<String>),
+    UserName,
+    UserEmail,
+    Branch,
+    Repo,
+    Remote,
+    ShortSha,
+    /// `{ticket}`, e.g. PAY-4821
+    Ticket,
+    /// `{ticket.key}`, e.g. PAY
+    TicketKey,
+    /// `{ticket.number}`, e.g. 4821
+    TicketNumber,
+    /// `{coauthors}`, people from `pair start` as `Name <email>`
+    Coauthors,
+}
+
+pub const CONTEXT_VARIABLE_NAMES: [&str; 11] = [
+    "date",
+    "user.name",
+    "user.email",
+    "branch",
+    "repo",
+    "remote",
+    "short_sha",
+    "ticket",
+    "ticket.key",
+    "ticket.number",
+    "coauthors",
+];
+
+impl ContextVariable {
+    /// Returns None when name isn't a built-in variable at all
+    pub fn parse(name: &str, arg: Option<&str>) -> Option<Result<ContextVariable, String>> {
+        let variable = match name {
+            "date" => {
+                return Some(match arg {
+                    None => Ok(ContextVariable::Date(None)),
+                    Some(format) => validate_date_format(format)
+                        .map(|_| ContextVariable::Date(Some(format.to_owned()))),
+                })
+            }
+            "user.name" => ContextVariable::UserName,
+            "user.email" => ContextVariable::UserEmail,
+            "branch" => ContextVariable::Branch,
+            "repo" => ContextVariable::Repo,
+            "remote" => ContextVariable::Remote,
+            "short_sha" => ContextVariable::ShortSha,
+            "ticket" => ContextVariable::Ticket,
+            "ticket.key" => ContextVariable::TicketKey,
+            "ticket.number" => ContextVariable::TicketNumber,
+            "coauthors" => ContextVariable::Coauthors,
+            _ => return None,
+        };
+
+        match arg {
+            None => Some(Ok(variable)),
+            Some(_) => Some(Err(format!("Built-in {} doesn't take an argument", name))),
+        }
+    }
+
+    pub fn is_ticket(&self) -> bool {
+        matches!(
+            self,
+            ContextVariable::Ticket | ContextVariable::TicketKey | ContextVariable::TicketNumber
+        )
+    }
+
+    /// `ticket` is looked up once and shared by all ticket variables
+    pub fn resolve(
+        &self,
+        data: &Data,
+        ticket: &mut Option<Option<Ticket>>,
+        from_clipboard: bool,
+    ) -> Option<String> {
+        match self {
+            ContextVariable::Date(format) => Some(
+                chrono::Local::now()
+                    .format(format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT))
+                    .to_string(),
+            ),
+            ContextVariable::UserName => git::config_value("user.name"),
+            ContextVariable::UserEmail => git::config_value("user.email"),
+            ContextVariable::Branch => git::current_branch().ok(),
+            ContextVariable::Repo => git::repo_name().ok(),
+            ContextVariable::Remote => git::remote_name().ok(),
+            ContextVariable::ShortSha => git::short_sha().ok(),
+            ContextVariable::Coauthors => {
+                Some(active_coauthors(data).join(", ")).filter(|coauthors| !coauthors.is_empty())
+            }
+            ContextVariable::Ticket
+            | ContextVariable::TicketKey
+            | ContextVariable::TicketNumber => {
+                let ticket =
+                    ticket.get_or_insert_with(|| find_current_ticket(data, from_clipboard));
+                ticket.as_ref().map(|ticket| match self {
+                    ContextVariable::TicketKey => ticket.key.to_owned(),
+                    ContextVariable::TicketNumber => ticket.number.to_owned(),
+                    _ => ticket.to_string(),
+                })
+            }
+        }
+    }
+}
+
+fn validate_date_format(format: &str) -> Result<(), String> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("Invalid date format `{}`", format));
+    }
+    Ok(())
+}
+
+impl Template {
+    pub fn context_variables(&self) -> Vec<&ContextVariable> {
+        self.placeholders()
+            .into_iter()
+            .filter_map(|(node, _)| match node {
+                Node::Context(variable, _) => Some(variable),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Runs git only for variables that are used in template,
+    /// ticket is looked for in clipboard when ticket_from_clipboard is on
+    pub fn resolve_context(&self, data: &Data) -> HashMap<ContextVariable, String> {
+        self.resolve_context_with(data, data.ticket_from_clipboard)
+    }
+
+    /// For hooks, which run on every `git commit` and never run paste command
+    pub fn resolve_context_without_clipboard(
+        &self,
+        data: &Data,
+    ) -> HashMap<ContextVariable, String> {
+        self.resolve_context_with(data, false)
+    }
+
+    fn resolve_context_with(
+        &self,
+        data: &Data,
+        from_clipboard: bool,
+    ) -> HashMap<ContextVariable, String> {
+        let mut context = HashMap::new();
+        let mut ticket = None;
+        for variable in self.context_variables() {
+            if context.contains_key(variable) {
+                continue;
+            }
+            if let Some(value) = variable.resolve(data, &mut ticket, from_clipboard) {
+                context.insert(variable.to_owned(), value);
+            }
+        }
+        context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_built_in_variables() {
+        assert_eq!(
+            ContextVariable::parse("user.email", None),
+            Some(Ok(ContextVariable::UserEmail))
+        );
+        assert_eq!(
+            ContextVariable::parse("date", Some("%d.%m.%Y")),
+            Some(Ok(ContextVariable::Date(Some("%d.%m.%Y".to_owned()))))
+        );
+        assert!(ContextVariable::parse("date", Some("%Q")).unwrap().is_err());
+        assert!(ContextVariable::parse("repo", Some("x")).unwrap().is_err());
+        assert_eq!(ContextVariable::parse("summary", None), None);
+    }
+}
@@ -0,0 +1,116 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Slug,
+    Upper,
+    Lower,
+    Snake,
+    Kebab,
+    Truncate(usize),
+}
+
+pub const FILTER_NAMES: [&str; 6] = ["slug", "upper", "lower", "snake", "kebab", "truncate:<n>"];
+
+impl Filter {
+    /// Parses single filter spec like `slug` or `truncate:50`
+    pub fn parse(spec: &str) -> Result<Filter, String> {
+        let (name, arg) = match spec.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (spec, None),
+        };
+
+        match (name, arg) {
+            ("slug", None) => Ok(Filter::Slug),
+            ("upper", None) => Ok(Filter::Upper),
+            ("lower", None) => Ok(Filter::Lower),
+            ("snake", None) => Ok(Filter::Snake),
+            ("kebab", None) => Ok(Filter::Kebab),
+            ("truncate", Some(len)) => len
+                .parse::<usize>()
+                .map(Filter::Truncate)
+                .map_err(|_| format!("Filter truncate expects a number, got `{}`", len)),
+            ("truncate", None) => Err("Filter truncate expects a length, e.g. truncate:50".into()),
+            (name, Some(_)) if FILTER_NAMES.contains(&name) => {
+                Err(format!("Filter {} doesn't take an argument", name))
+            }
+            _ => Err(format!(
+                "Unknown filter `{}`, available filters: {}",
+                spec,
+                FILTER_NAMES.join(", ")
+            )),
+        }
+    }
+
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            Filter::Slug => value
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<&str>>()
+                .join("-")
+                .to_lowercase(),
+            Filter::Upper => value.to_uppercase(),
+            Filter::Lower => value.to_lowercase(),
+            Filter::Snake => split_words(value).join("_"),
+            Filter::Kebab => split_words(value).join("-"),
+            Filter::Truncate(len) => value
+                .chars()
+                .take(*len)
+                .collect::<String>()
+                .trim_end()
+                .into(),
+        }
+    }
+}
+
+/// Splits on anything that's not alphanumeric and on camelCase boundaries,
+/// returned words are lowercased
+fn split_words(value: &str) -> Vec<String> {
+    let mut words: Vec<String> = vec![];
+    let mut current = String::new();
+    let mut previous_lowercase = false;
+
+    for c in value.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lowercase = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lowercase && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lowercase = c.is_lowercase() || c.is_numeric();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_filters() {
+        assert_eq!(Filter::parse("slug"), Ok(Filter::Slug));
+        assert_eq!(Filter::parse("truncate:50"), Ok(Filter::Truncate(50)));
+        assert!(Filter::parse("slg").is_err());
+        assert!(Filter::parse("truncate").is_err());
+        assert!(Filter::parse("truncate:abc").is_err());
+        assert!(Filter::parse("upper:1").is_err());
+    }
+
+    #[test]
+    fn applies_filters() {
+        assert_eq!(Filter::Slug.apply(" Fix: login, page! "), "fix-login-page");
+        assert_eq!(Filter::Upper.apply("pay-12"), "PAY-12");
+        assert_eq!(Filter::Lower.apply("PAY-12"), "pay-12");
+        assert_eq!(Filter::Snake.apply("newCli for stuff"), "new_cli_for_stuff");
+        assert_eq!(Filter::Kebab.apply("new_cli ForStuff"), "new-cli-for-stuff");
+        assert_eq!(Filter::Truncate(8).apply("fix the login"), "fix the");
+    }
+}
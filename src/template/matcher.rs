@@ -0,0 +1,96 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use super::{placeholder_of, Node, Template};
+
+impl Template {
+    /// Regex matching any text the template can render to. `validation` gives
+    /// regex of arg under key, `{}` are keyed by position like in template args.
+    /// Filters change values, so their validation isn't used
+    pub fn matcher(&self, validation: &dyn Fn(&str) -> Option<String>) -> Result<Regex> {
+        let mut position = 0;
+        let pattern = nodes_pattern(&self.nodes, validation, &mut position);
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map_err(|err| anyhow!("Couldn't build matcher of template: {}", err))
+    }
+}
+
+fn nodes_pattern(
+    nodes: &[Node],
+    validation: &dyn Fn(&str) -> Option<String>,
+    position: &mut usize,
+) -> String {
+    let mut pattern = String::new();
+    for (index, node) in nodes.iter().enumerate() {
+        let placeholder = match node {
+            Node::Literal(text) => {
+                // Space before a part that can be left out is left out too,
+                // as git strips trailing whitespace of the message
+                let next_is_optional = match nodes.get(index + 1) {
+                    Some(Node::Section(_)) => true,
+                    Some(next) => placeholder_of(next).is_some_and(|next| next.optional),
+                    None => false,
+                };
+                let kept = if next_is_optional {
+                    text.trim_end()
+                } else {
+                    text
+                };
+                pattern.push_str(&regex::escape(kept));
+                if kept.len() < text.len() {
+                    pattern.push_str(&format!("(?:{})?", regex::escape(&text[kept.len()..])));
+                }
+                continue;
+            }
+            Node::Section(section) => {
+                pattern.push_str(&format!(
+                    "(?:{})?",
+                    nodes_pattern(&section.nodes, validation, position)
+                ));
+                continue;
+            }
+            _ => placeholder_of(node).unwrap(),
+        };
+
+        let key = match node {
+            Node::Positional(_) => {
+                *position += 1;
+                Some(position.to_string())
+            }
+            Node::Named(placeholder) | Node::Context(_, placeholder) => {
+                Some(placeholder.name.to_owned())
+            }
+            _ => None,
+        };
+        let value_pattern = key
+            .filter(|_| placeholder.filters.is_empty())
+            .and_then(|key| validation(&key))
+            .map(|validation| format!("(?:{})", validation))
+            .unwrap_or_else(|| ".+?".to_owned());
+
+        if placeholder.optional {
+            pattern.push_str(&format!("(?:{})?", value_pattern));
+        } else {
+            pattern.push_str(&value_pattern);
+        }
+    }
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_what_template_renders_to() {
+        let template = Template::parse("[{ticket}] {type=fix}{?({scope})}: {} {note?}").unwrap();
+        let validation = |key: &str| (key == "ticket").then(|| r"[A-Z]+-\d+".to_owned());
+        let matcher = template.matcher(&validation).unwrap();
+
+        assert!(matcher.is_match("[PAY-12] fix(api): login works"));
+        assert!(matcher.is_match("[PAY-12] feat: add login form later"));
+        assert!(!matcher.is_match("[pay] fix: login"));
+        assert!(!matcher.is_match("[PAY-12] fix login"));
+        assert!(!matcher.is_match("[PAY-12] fix: login\nmore"));
+    }
+}
@@ -0,0 +1,724 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Error, Result};
+use regex::Regex;
+
+use crate::ticket::Ticket;
+
+pub mod context;
+pub mod filters;
+pub mod matcher;
+pub mod parser;
+pub mod variant;
+
+pub use context::ContextVariable;
+pub use filters::Filter;
+pub use parser::{Node, Placeholder, Template, TemplateError};
+pub use variant::{arg_label, templates_help, TemplateArg, TemplateVariant};
+
+#[derive(Clone)]
+pub struct InterpolationValues {
+    pub positional: Vec<String>,
+    pub named: HashMap<String, String>,
+    pub branch_number: Option<String>,
+    pub autocomplete: Option<Vec<String>>,
+    pub context: HashMap<ContextVariable, String>,
+}
+
+impl InterpolationValues {
+    /// Explicitly given `name=value` wins over value taken from git,
+    /// given `ticket=` is also used for `{ticket.key}` and `{ticket.number}`
+    pub fn context_value(&self, variable: &ContextVariable, name: &str) -> Option<String> {
+        if let Some(value) = self.named.get(name) {
+            return Some(value.to_owned());
+        }
+        if let (ContextVariable::TicketKey | ContextVariable::TicketNumber, Some(ticket)) =
+            (variable, self.named.get("ticket"))
+        {
+            let ticket = Ticket::from_id(ticket);
+            return Some(match variable {
+                ContextVariable::TicketKey => ticket.key,
+                _ => ticket.number,
+            });
+        }
+        self.context.get(variable).cloned()
+    }
+}
+
+/// Arguments in form of `name=value` are treated as values for `{name}`,
+/// everything else fills `{}` in order. `name\=value` is taken as `{}` value
+/// `name=value`, so values starting like that can still be given.
+impl From<Vec<String>> for InterpolationValues {
+    fn from(values: Vec<String>) -> Self {
+        let named_value_regex = Regex::new(r"(?s)^([a-zA-Z_][a-zA-Z0-9_]*)(\\?)=(.*)$").unwrap();
+        let mut positional = vec![];
+        let mut named = HashMap::new();
+
+        for value in values {
+            match named_value_regex.captures(&value) {
+                Some(captures) if captures[2].is_empty() => {
+                    named.insert(captures[1].to_owned(), captures[3].to_owned());
+                }
+                Some(captures) => positional.push(format!("{}={}", &captures[1], &captures[3])),
+                None => positional.push(value),
+            }
+        }
+
+        InterpolationValues {
+            positional,
+            named,
+            branch_number: None,
+            autocomplete: None,
+            context: HashMap::new(),
+        }
+    }
+}
+
+fn placeholder_of(node: &Node) -> Option<&Placeholder> {
+    match node {
+        Node::Positional(placeholder)
+        | Node::Named(placeholder)
+        | Node::BranchNumber(placeholder)
+        | Node::Autocomplete(placeholder)
+        | Node::Context(_, placeholder) => Some(placeholder),
+        Node::Literal(_) | Node::Section(_) => None,
+    }
+}
+
+/// Placeholders in sections are never required, section is dropped instead
+fn collect_placeholders<'a>(nodes: &'a [Node], in_section: bool, out: &mut Vec<(&'a Node, bool)>) {
+    for node in nodes {
+        match node {
+            Node::Literal(_) => {}
+            Node::Section(section) => collect_placeholders(&section.nodes, true, out),
+            _ => {
+                let required = !in_section && placeholder_of(node).unwrap().is_required();
+                out.push((node, required));
+            }
+        }
+    }
+}
+
+/// Assigns values to slots in order, optional slots are filled only when
+/// there are more values than required slots
+fn assign_to_slots<'a>(slots: &[bool], values: &'a [String]) -> Vec<Option<&'a String>> {
+    let required = slots.iter().filter(|required| **required).count();
+    let mut optional_to_fill = values.len().saturating_sub(required);
+    let mut values = values.iter();
+
+    slots
+        .iter()
+        .map(|required| {
+            if *required {
+                values.next()
+            } else if optional_to_fill > 0 {
+                optional_to_fill -= 1;
+                values.next()
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn expected_count(required: usize, total: usize) -> String {
+    if required == total {
+        required.to_string()
+    } else {
+        format!("between {} and {}", required, total)
+    }
+}
+
+struct Renderer<'a> {
+    values: &'a InterpolationValues,
+    positional: std::vec::IntoIter<Option<&'a String>>,
+    autocomplete: std::vec::IntoIter<Option<&'a String>>,
+    default_filter: fn(&str) -> String,
+}
+
+impl Renderer<'_> {
+    /// Returns false when any placeholder was left empty
+    fn render_nodes(&mut self, nodes: &[Node], in_section: bool, out: &mut String) -> Result<bool> {
+        let mut all_filled = true;
+
+        for node in nodes {
+            let placeholder = match node {
+                Node::Literal(text) => {
+                    out.push_str(text);
+                    continue;
+                }
+                Node::Section(section) => {
+                    let mut rendered_section = String::new();
+                    if self.render_nodes(&section.nodes, true, &mut rendered_section)? {
+                        out.push_str(&rendered_section);
+                    }
+                    continue;
+                }
+                _ => placeholder_of(node).unwrap(),
+            };
+
+            let value = match node {
+                Node::Positional(_) => self.positional.next().flatten().cloned(),
+                Node::Named(_) => self.values.named.get(&placeholder.name).cloned(),
+                Node::BranchNumber(_) => self.values.branch_number.clone(),
+                Node::Autocomplete(_) => self.autocomplete.next().flatten().cloned(),
+                Node::Context(variable, _) => {
+                    self.values.context_value(variable, &placeholder.name)
+                }
+                Node::Literal(_) | Node::Section(_) => unreachable!(),
+            };
+            let value = value.or_else(|| placeholder.default.clone());
+
+            match value {
+                Some(value) if !value.is_empty() => {
+                    out.push_str(&self.apply_filters(&value, placeholder))
+                }
+                Some(_) => all_filled = false,
+                None if in_section || !placeholder.is_required() => all_filled = false,
+                None => {
+                    return Err(match node {
+                        Node::Named(_) => anyhow!("No value for {{{}}}", placeholder.name),
+                        Node::BranchNumber(_) => anyhow!("No number from branch for {{b}}"),
+                        Node::Autocomplete(_) => anyhow!(
+                            "No autocomplete value for {{[]}} at column {}",
+                            placeholder.column
+                        ),
+                        Node::Context(_, _) => anyhow!(
+                            "Couldn't get value of {{{}}}, pass it as {}=value",
+                            placeholder.name,
+                            placeholder.name
+                        ),
+                        _ => anyhow!("No value for {{}} at column {}", placeholder.column),
+                    })
+                }
+            }
+        }
+
+        Ok(all_filled)
+    }
+
+    fn apply_filters(&self, value: &str, placeholder: &Placeholder) -> String {
+        if placeholder.filters.is_empty() {
+            return (self.default_filter)(value);
+        }
+        placeholder
+            .filters
+            .iter()
+            .fold(value.to_owned(), |value, filter| filter.apply(&value))
+    }
+}
+
+impl Template {
+    pub(crate) fn placeholders(&self) -> Vec<(&Node, bool)> {
+        let mut placeholders = vec![];
+        collect_placeholders(&self.nodes, false, &mut placeholders);
+        placeholders
+    }
+
+    /// Returns whether each slot of given kind is required, in order
+    fn slots(&self, is_kind: fn(&Node) -> bool) -> Vec<bool> {
+        self.placeholders()
+            .into_iter()
+            .filter(|(node, _)| is_kind(node))
+            .map(|(_, required)| required)
+            .collect()
+    }
+
+    pub fn positional_count(&self) -> usize {
+        self.slots(|node| matches!(node, Node::Positional(_))).len()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = vec![];
+        for (node, _) in self.placeholders() {
+            if let Node::Named(placeholder) = node {
+                if !names.contains(&placeholder.name.as_str()) {
+                    names.push(&placeholder.name);
+                }
+            }
+        }
+        names
+    }
+
+    pub fn required_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = vec![];
+        for (node, required) in self.placeholders() {
+            if let (Node::Named(placeholder), true) = (node, required) {
+                if !names.contains(&placeholder.name.as_str()) {
+                    names.push(&placeholder.name);
+                }
+            }
+        }
+        names
+    }
+
+    pub fn has_branch_number(&self) -> bool {
+        !self
+            .slots(|node| matches!(node, Node::BranchNumber(_)))
+            .is_empty()
+    }
+
+    pub fn autocomplete_count(&self) -> usize {
+        self.slots(|node| matches!(node, Node::Autocomplete(_)))
+            .len()
+    }
+
+    pub fn validate(&self, values: &InterpolationValues) -> Result<()> {
+        let mut problems: Vec<String> = vec![];
+
+        let positional_slots = self.slots(|node| matches!(node, Node::Positional(_)));
+        let required_positional = positional_slots.iter().filter(|r| **r).count();
+        if values.positional.len() < required_positional
+            || values.positional.len() > positional_slots.len()
+        {
+            problems.push(format!(
+                "Number of places to interpolate doesn't match with number of args provided.
+       Expected {}
+       Received {}",
+                expected_count(required_positional, positional_slots.len()),
+                values.positional.len()
+            ));
+        } else {
+            let empty: Vec<String> = assign_to_slots(&positional_slots, &values.positional)
+                .into_iter()
+                .zip(&positional_slots)
+                .enumerate()
+                .filter(|(_, (value, required))| **required && value.is_some_and(|v| v.is_empty()))
+                .map(|(index, _)| (index + 1).to_string())
+                .collect();
+            if !empty.is_empty() {
+                problems.push(format!(
+                    "Empty values for {{}} at positions: {}",
+                    empty.join(", ")
+                ));
+            }
+        }
+
+        let names = self.names();
+        let context_names: Vec<&str> = self
+            .placeholders()
+            .into_iter()
+            .filter_map(|(node, _)| match node {
+                Node::Context(ContextVariable::TicketKey | ContextVariable::TicketNumber, _) => {
+                    Some("ticket")
+                }
+                Node::Context(_, placeholder) => Some(placeholder.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        // Empty value fills only optional places or ones with default
+        let missing: Vec<&str> = self
+            .required_names()
+            .into_iter()
+            .filter(|name| values.named.get(*name).is_none_or(|value| value.is_empty()))
+            .collect();
+        if !missing.is_empty() {
+            problems.push(format!("Missing values for: {}", missing.join(", ")));
+        }
+
+        let mut unknown: Vec<&str> = values
+            .named
+            .keys()
+            .filter(|key| !names.contains(&key.as_str()) && !context_names.contains(&key.as_str()))
+            .map(|key| key.as_str())
+            .collect();
+        unknown.sort();
+        if !unknown.is_empty() {
+            problems.push(format!(
+                "Unknown names provided: {} \n Template has following names: {} \n \
+                To pass value with = for {{}}, escape it like {}\\=...",
+                unknown.join(", "),
+                names.join(", "),
+                unknown[0]
+            ));
+        }
+
+        for (node, required) in self.placeholders() {
+            if let (Node::Context(variable, placeholder), true) = (node, required) {
+                if values
+                    .context_value(variable, &placeholder.name)
+                    .is_none_or(|value| value.is_empty())
+                {
+                    let mut problem = format!(
+                        "Couldn't get value of {{{}}}, pass it as {}=value",
+                        placeholder.name, placeholder.name
+                    );
+                    if variable.is_ticket() {
+                        problem.push_str(
+                            ". Clipboard is searched for it only after set-ticket-from-clipboard on",
+                        );
+                    }
+                    problems.push(problem);
+                }
+            }
+        }
+
+        let branch_number_slots = self.slots(|node| matches!(node, Node::BranchNumber(_)));
+        match (branch_number_slots.contains(&true), &values.branch_number) {
+            (true, None) => problems
+                .push("Template uses {b}, run with -b to take it from your branch".to_owned()),
+            _ if branch_number_slots.is_empty() && values.branch_number.is_some() => problems
+                .push("Number from branch was requested, but template has no {b}".to_owned()),
+            _ => {}
+        }
+
+        let autocomplete_slots = self.slots(|node| matches!(node, Node::Autocomplete(_)));
+        let required_autocomplete = autocomplete_slots.iter().filter(|r| **r).count();
+        match &values.autocomplete {
+            None if required_autocomplete > 0 => problems.push(
+                "Template uses {[]}, run with -a to fill it with autocomplete values".to_owned(),
+            ),
+            Some(autocomplete)
+                if autocomplete.len() < required_autocomplete
+                    || autocomplete.len() > autocomplete_slots.len() =>
+            {
+                problems.push(format!(
+                    "Number of autocomplete places doesn't match with number of autocomplete values.
+       Expected {}
+       Stored {}
+       You can change stored values with set-auto-complete",
+                    expected_count(required_autocomplete, autocomplete_slots.len()),
+                    autocomplete.len()
+                ))
+            }
+            _ => {}
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::msg(format!("\n {}", problems.join("\n "))))
+        }
+    }
+
+    pub fn render(&self, values: &InterpolationValues) -> Result<String> {
+        self.render_with_default_filter(values, |value| value.to_owned())
+    }
+
+    /// `default_filter` is used on values of placeholders that have no filters
+    pub fn render_with_default_filter(
+        &self,
+        values: &InterpolationValues,
+        default_filter: fn(&str) -> String,
+    ) -> Result<String> {
+        let positional_slots = self.slots(|node| matches!(node, Node::Positional(_)));
+        let autocomplete_slots = self.slots(|node| matches!(node, Node::Autocomplete(_)));
+        let autocomplete_values = values.autocomplete.as_deref().unwrap_or_default();
+
+        let mut renderer = Renderer {
+            values,
+            positional: assign_to_slots(&positional_slots, &values.positional).into_iter(),
+            autocomplete: assign_to_slots(&autocomplete_slots, autocomplete_values).into_iter(),
+            default_filter,
+        };
+
+        let mut rendered = String::new();
+        renderer.render_nodes(&self.nodes, false, &mut rendered)?;
+
+        Ok(rendered)
+    }
+}
+
+pub fn validate_interpolation_places_count(
+    format: &str,
+    values: &InterpolationValues,
+) -> Result<()> {
+    Template::parse(format)?.validate(values)
+}
+
+pub fn interpolate(format: &str, values: impl Into<InterpolationValues>) -> Result<String> {
+    Template::parse(format)?.render(&values.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate_no_placeholders() {
+        let format = String::from("Hello, world!");
+        let values = vec![];
+        let result = interpolate(&format, values);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "Hello, world!");
+    }
+
+    #[test]
+    fn test_interpolate_single_placeholder() {
+        let format = String::from("Hello, {}!");
+        let values = vec![String::from("world")];
+        let result = interpolate(&format, values);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "Hello, world!");
+    }
+
+    #[test]
+    fn test_interpolate_multiple_placeholders() {
+        let format = String::from("Hello, {}, you are {} years old.");
+        let values = vec![String::from("John"), String::from("30")];
+        let result = interpolate(&format, values);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "Hello, John, you are 30 years old.");
+    }
+
+    #[test]
+    fn test_interpolate_not_enough_values() {
+        let format = String::from("Hello, {}!");
+        let values: Vec<String> = vec![];
+        let result = validate_interpolation_places_count(&format, &values.into());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_interpolate_too_many_values() {
+        let format = String::from("Hello, {}!");
+        let values = vec![String::from("world"), String::from("extra")];
+        let result = validate_interpolation_places_count(&format, &values.into());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_interpolate_named_placeholders_reused() {
+        let format = String::from("[{ticket}] {summary} ({ticket})");
+        let values = vec![
+            String::from("summary=fix login"),
+            String::from("ticket=123"),
+        ];
+        let result = interpolate(&format, values);
+        assert_eq!(result.unwrap(), "[123] fix login (123)");
+    }
+
+    #[test]
+    fn test_interpolate_named_and_positional_placeholders() {
+        let format = String::from("{scope}: {} - {}");
+        let values = vec![
+            String::from("first"),
+            String::from("scope=cli"),
+            String::from("second"),
+        ];
+        let result = interpolate(&format, values);
+        assert_eq!(result.unwrap(), "cli: first - second");
+    }
+
+    #[test]
+    fn test_interpolate_branch_number_in_single_pass() {
+        let format = String::from("[{b}] {}");
+        let mut values: InterpolationValues = vec![String::from("{b}")].into();
+        values.branch_number = Some(String::from("123"));
+        let result = interpolate(&format, values);
+        assert_eq!(result.unwrap(), "[123] {b}");
+    }
+
+    #[test]
+    fn test_interpolate_escaped_braces() {
+        let format = String::from("{{{}}} {}");
+        let values = vec![String::from("a"), String::from("b")];
+        let result = interpolate(&format, values);
+        assert_eq!(result.unwrap(), "{a} b");
+    }
+
+    #[test]
+    fn test_interpolate_autocomplete_values_in_order() {
+        let format = String::from("{[]}/{[]}: {}");
+        let mut values: InterpolationValues = vec![String::from("msg")].into();
+        values.autocomplete = Some(vec![String::from("web"), String::from("api")]);
+        assert!(validate_interpolation_places_count(&format, &values).is_ok());
+        assert_eq!(interpolate(&format, values).unwrap(), "web/api: msg");
+    }
+
+    #[test]
+    fn test_validate_autocomplete_count() {
+        let format = String::from("{[]}/{[]}: {}");
+        let mut values: InterpolationValues = vec![String::from("msg")].into();
+        assert!(validate_interpolation_places_count(&format, &values).is_err());
+        values.autocomplete = Some(vec![String::from("web")]);
+        assert!(validate_interpolation_places_count(&format, &values).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_with_filters() {
+        let format = String::from("feature/{ticket|upper}-{summary|slug|truncate:12}");
+        let values = vec![
+            String::from("ticket=pay-12"),
+            String::from("summary=Fix the Login page"),
+        ];
+        let result = interpolate(&format, values);
+        assert_eq!(result.unwrap(), "feature/PAY-12-fix-the-logi");
+    }
+
+    #[test]
+    fn test_default_filter_skips_placeholders_with_filters() {
+        let template = Template::parse("{a}/{x|snake}").unwrap();
+        let values: InterpolationValues =
+            vec![String::from("a=new cli"), String::from("x=new cli")].into();
+        let result = template.render_with_default_filter(&values, |v| v.replace(' ', "-"));
+        assert_eq!(result.unwrap(), "new-cli/new_cli");
+    }
+
+    #[test]
+    fn test_interpolate_optional_scope_drops_brackets() {
+        let format = String::from("feat({scope?}): {msg}");
+        let values = vec![String::from("msg=add login")];
+        assert_eq!(interpolate(&format, values).unwrap(), "feat: add login");
+
+        let values = vec![String::from("msg=add login"), String::from("scope=cli")];
+        assert_eq!(
+            interpolate(&format, values).unwrap(),
+            "feat(cli): add login"
+        );
+    }
+
+    #[test]
+    fn test_interpolate_optional_built_in_drops_brackets() {
+        let format = String::from("feat({ticket?}): {}");
+        let values = vec![String::from("add login")];
+        assert_eq!(interpolate(&format, values).unwrap(), "feat: add login");
+
+        let values = vec![String::from("add login"), String::from("ticket=PAY-1")];
+        assert_eq!(
+            interpolate(&format, values).unwrap(),
+            "feat(PAY-1): add login"
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_empty_required_values() {
+        let template = Template::parse("{type=chore}: {msg} {note?} {}").unwrap();
+        let values: InterpolationValues = vec![
+            String::from("type="),
+            String::from("msg="),
+            String::from("note="),
+            String::new(),
+        ]
+        .into();
+        let err = template.validate(&values).unwrap_err().to_string();
+        assert!(err.contains("Missing values for: msg"));
+        assert!(err.contains("Empty values for {} at positions: 1"));
+
+        let values: InterpolationValues = vec![
+            String::from("type="),
+            String::from("msg=fix"),
+            String::from("x"),
+        ]
+        .into();
+        template.validate(&values).unwrap();
+    }
+
+    #[test]
+    fn test_interpolate_default_values() {
+        let format = String::from("{type=chore}: {msg}");
+        let values = vec![String::from("msg=bump deps")];
+        assert_eq!(interpolate(&format, values).unwrap(), "chore: bump deps");
+
+        let values = vec![String::from("msg=add login"), String::from("type=feat")];
+        assert_eq!(interpolate(&format, values).unwrap(), "feat: add login");
+    }
+
+    #[test]
+    fn test_interpolate_drops_section_with_empty_placeholder() {
+        let format = String::from("{}{? - refs {ticket} ({b})}");
+        let mut values: InterpolationValues =
+            vec![String::from("msg"), String::from("ticket=PAY-1")].into();
+        assert_eq!(
+            interpolate(&format, values.positional.clone()).unwrap(),
+            "msg"
+        );
+        values.branch_number = Some(String::from("12"));
+        assert!(validate_interpolation_places_count(&format, &values).is_ok());
+        assert_eq!(
+            interpolate(&format, values).unwrap(),
+            "msg - refs PAY-1 (12)"
+        );
+    }
+
+    #[test]
+    fn test_interpolate_optional_positional_filled_after_required() {
+        let format = String::from("{?}{}");
+        let values: InterpolationValues = vec![String::from("only")].into();
+        assert!(validate_interpolation_places_count(&format, &values).is_ok());
+        assert_eq!(interpolate(&format, values).unwrap(), "only");
+
+        let values = vec![String::from("a"), String::from("b")];
+        assert_eq!(interpolate(&format, values).unwrap(), "ab");
+    }
+
+    #[test]
+    fn test_validate_counts_optional_positional() {
+        let format = String::from("{} {?} {=x}");
+        let values: InterpolationValues = vec![].into();
+        let err = validate_interpolation_places_count(&format, &values)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Expected between 1 and 3"));
+        let values: InterpolationValues = vec![String::from("a"), String::from("b")].into();
+        assert!(validate_interpolation_places_count(&format, &values).is_ok());
+    }
+
+    #[test]
+    fn test_interpolate_context_variables() {
+        let template = Template::parse("{} by {user.name} on {branch}").unwrap();
+        let mut values: InterpolationValues =
+            vec![String::from("msg"), String::from("branch=main")].into();
+        values
+            .context
+            .insert(ContextVariable::UserName, String::from("Zolw"));
+        values
+            .context
+            .insert(ContextVariable::Branch, String::from("feature/1"));
+        assert!(template.validate(&values).is_ok());
+        assert_eq!(template.render(&values).unwrap(), "msg by Zolw on main");
+
+        values.context.remove(&ContextVariable::UserName);
+        assert!(template.validate(&values).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_ticket_parts_from_given_ticket() {
+        let format = String::from("{ticket.key}/{ticket.number}: {}");
+        let values: InterpolationValues =
+            vec![String::from("msg"), String::from("ticket=pay-12")].into();
+        assert!(validate_interpolation_places_count(&format, &values).is_ok());
+        assert_eq!(interpolate(&format, values).unwrap(), "PAY/12: msg");
+    }
+
+    #[test]
+    fn test_validate_requires_branch_number_for_b() {
+        let format = String::from("[{b}] {}");
+        let values: InterpolationValues = vec![String::from("msg")].into();
+        assert!(validate_interpolation_places_count(&format, &values).is_err());
+    }
+
+    #[test]
+    fn test_validate_reports_missing_and_unknown_names() {
+        let format = String::from("[{ticket}] {scope}: {summary}");
+        let values: InterpolationValues =
+            vec![String::from("ticket=1"), String::from("summry=typo")].into();
+        let err = validate_interpolation_places_count(&format, &values)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Missing values for: scope, summary"));
+        assert!(err.contains("Unknown names provided: summry"));
+    }
+
+    #[test]
+    fn test_escaped_equals_sign_is_positional_value() {
+        let format = String::from("{}");
+        let values = vec![String::from("a\\=b is my message")];
+        let result = interpolate(&format, values);
+        assert_eq!(result.unwrap(), "a=b is my message");
+
+        let values: InterpolationValues = vec![String::from("a=b is my message")].into();
+        let err = validate_interpolation_places_count(&format, &values)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("escape it like a\\=..."));
+    }
+
+    #[test]
+    fn test_validate_named_placeholders() {
+        let format = String::from("[{ticket}] {summary}");
+        let values: InterpolationValues =
+            vec![String::from("summary=fix"), String::from("ticket=1")].into();
+        assert!(validate_interpolation_places_count(&format, &values).is_ok());
+    }
+}
@@ -0,0 +1,550 @@
+use std::fmt;
+
+use super::{
+    context::{ContextVariable, CONTEXT_VARIABLE_NAMES},
+    filters::Filter,
+    placeholder_of,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    pub name: String,
+    /// 1-based column of the opening `{` in template
+    pub column: usize,
+    /// Applied in order after value is resolved, e.g. `{summary|slug}`
+    pub filters: Vec<Filter>,
+    /// `{scope?}` renders as empty when no value is given
+    pub optional: bool,
+    /// `{type=chore}` is used when no value is given
+    pub default: Option<String>,
+}
+
+impl Placeholder {
+    pub fn is_required(&self) -> bool {
+        !self.optional && self.default.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Literal(String),
+    /// `{}`
+    Positional(Placeholder),
+    /// `{name}`
+    Named(Placeholder),
+    /// `{b}`
+    BranchNumber(Placeholder),
+    /// `{[]}`
+    Autocomplete(Placeholder),
+    /// Built-in variable, e.g. `{user.name}` or `{date:%Y-%m-%d}`
+    Context(ContextVariable, Placeholder),
+    /// `{?...}` is dropped as a whole when any placeholder inside is empty
+    Section(Section),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub nodes: Vec<Node>,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TemplateError {
+    pub message: String,
+    pub column: usize,
+    pub template: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at column {}\n    {}\n    {}^",
+            self.message,
+            self.column,
+            self.template,
+            " ".repeat(self.column.saturating_sub(1))
+        )
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Placeholder { body: String, column: usize },
+    SectionStart { column: usize },
+    SectionEnd,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let error = |message: &str, column: usize| TemplateError {
+        message: message.to_owned(),
+        column,
+        template: source.to_owned(),
+    };
+
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut open_sections: Vec<usize> = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '{' if chars.get(i + 1) == Some(&'{') => {
+                text.push('{');
+                i += 2;
+            }
+            '}' if chars.get(i + 1) == Some(&'}') => {
+                text.push('}');
+                i += 2;
+            }
+            '}' if !open_sections.is_empty() => {
+                open_sections.pop();
+                if !text.is_empty() {
+                    tokens.push(Token::Text(std::mem::take(&mut text)));
+                }
+                tokens.push(Token::SectionEnd);
+                i += 1;
+            }
+            '}' => return Err(error("Unmatched `}`, use `}}` for a literal brace", i + 1)),
+            '{' if chars.get(i + 1) == Some(&'?') && chars.get(i + 2) != Some(&'}') => {
+                open_sections.push(i + 1);
+                if !text.is_empty() {
+                    tokens.push(Token::Text(std::mem::take(&mut text)));
+                }
+                tokens.push(Token::SectionStart { column: i + 1 });
+                i += 2;
+            }
+            '{' => {
+                let column = i + 1;
+                let mut body = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(error(
+                                "Placeholder is never closed, use `{{` for a literal brace",
+                                column,
+                            ))
+                        }
+                        Some('{') => {
+                            return Err(error("Unexpected `{` inside of placeholder", i + 1))
+                        }
+                        Some('}') => break,
+                        Some(c) => body.push(*c),
+                    }
+                    i += 1;
+                }
+                i += 1;
+
+                if !text.is_empty() {
+                    tokens.push(Token::Text(std::mem::take(&mut text)));
+                }
+                tokens.push(Token::Placeholder { body, column });
+            }
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    if let Some(column) = open_sections.pop() {
+        return Err(error("Section is never closed", column));
+    }
+
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+
+    Ok(tokens)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn parse_placeholder(body: String, column: usize, source: &str) -> Result<Node, TemplateError> {
+    let error = |message: String, column: usize| TemplateError {
+        message,
+        column,
+        template: source.to_owned(),
+    };
+
+    let mut parts = body.split('|');
+    let head = parts.next().unwrap_or_default();
+    // Column right after `{` and placeholder name
+    let mut part_column = column + 1 + head.chars().count();
+    let mut filters = vec![];
+    for spec in parts {
+        part_column += 1;
+        filters.push(Filter::parse(spec).map_err(|message| error(message, part_column))?);
+        part_column += spec.chars().count();
+    }
+
+    let (head, optional, default) = match head.split_once('=') {
+        Some((name, default)) => (name, false, Some(default.to_owned())),
+        None => match head.strip_suffix('?') {
+            Some(name) => (name, true, None),
+            None => (head, false, None),
+        },
+    };
+
+    let (head, arg) = match head.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (head, None),
+    };
+
+    let placeholder = Placeholder {
+        name: head.to_owned(),
+        column,
+        filters,
+        optional,
+        default,
+    };
+
+    if let Some(variable) = ContextVariable::parse(head, arg) {
+        return variable
+            .map(|variable| Node::Context(variable, placeholder))
+            .map_err(|message| error(message, column));
+    }
+    if head.contains('.') {
+        return Err(error(
+            format!(
+                "Unknown built-in variable `{}`, available ones: {}",
+                head,
+                CONTEXT_VARIABLE_NAMES.join(", ")
+            ),
+            column,
+        ));
+    }
+    if arg.is_some() {
+        return Err(error(
+            format!("Only built-in date takes an argument, got `{{{}}}`", body),
+            column,
+        ));
+    }
+
+    match head {
+        "" => Ok(Node::Positional(placeholder)),
+        "b" => Ok(Node::BranchNumber(placeholder)),
+        "[]" => Ok(Node::Autocomplete(placeholder)),
+        name if is_identifier(name) => Ok(Node::Named(placeholder)),
+        _ => Err(error(
+            format!(
+                "Invalid placeholder `{{{}}}`, expected {{}}, {{name}}, {{name?}}, {{name=default}}, {{b}} or {{[]}}",
+                body
+            ),
+            column,
+        )),
+    }
+}
+
+/// `feat({scope?}): {msg}` drops the parentheses together with an empty scope,
+/// so optional placeholder wrapped directly in brackets becomes a section
+fn wrap_optional_in_brackets(nodes: Vec<Node>) -> Vec<Node> {
+    let mut wrapped: Vec<Node> = vec![];
+    let mut nodes = nodes.into_iter().peekable();
+
+    while let Some(node) = nodes.next() {
+        let placeholder = match placeholder_of(&node) {
+            Some(p) if p.optional => p,
+            _ => {
+                wrapped.push(node);
+                continue;
+            }
+        };
+
+        let opening = match wrapped.last() {
+            Some(Node::Literal(text)) => text.chars().last(),
+            _ => None,
+        };
+        let closing = match opening {
+            Some('(') => ')',
+            Some('[') => ']',
+            _ => {
+                wrapped.push(node);
+                continue;
+            }
+        };
+        let is_closed =
+            matches!(nodes.peek(), Some(Node::Literal(text)) if text.starts_with(closing));
+        if !is_closed {
+            wrapped.push(node);
+            continue;
+        }
+
+        let column = placeholder.column - 1;
+        if let Some(Node::Literal(text)) = wrapped.last_mut() {
+            text.pop();
+            if text.is_empty() {
+                wrapped.pop();
+            }
+        }
+        if let Some(Node::Literal(text)) = nodes.peek_mut() {
+            text.remove(0);
+            if text.is_empty() {
+                nodes.next();
+            }
+        }
+        wrapped.push(Node::Section(Section {
+            nodes: vec![
+                Node::Literal(opening.unwrap().to_string()),
+                node,
+                Node::Literal(closing.to_string()),
+            ],
+            column,
+        }));
+    }
+
+    wrapped
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        // Last element is the list of nodes currently being filled
+        let mut stack: Vec<(Vec<Node>, usize)> = vec![(vec![], 0)];
+
+        for token in tokenize(source)? {
+            match token {
+                Token::Text(text) => stack.last_mut().unwrap().0.push(Node::Literal(text)),
+                Token::Placeholder { body, column } => {
+                    let node = parse_placeholder(body, column, source)?;
+                    stack.last_mut().unwrap().0.push(node);
+                }
+                Token::SectionStart { column } => stack.push((vec![], column)),
+                Token::SectionEnd => {
+                    let (nodes, column) = stack.pop().unwrap();
+                    stack.last_mut().unwrap().0.push(Node::Section(Section {
+                        nodes: wrap_optional_in_brackets(nodes),
+                        column,
+                    }));
+                }
+            }
+        }
+
+        let (nodes, _) = stack.pop().unwrap();
+
+        Ok(Template {
+            nodes: wrap_optional_in_brackets(nodes),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholder(name: &str, column: usize) -> Placeholder {
+        Placeholder {
+            name: name.to_owned(),
+            column,
+            filters: vec![],
+            optional: false,
+            default: None,
+        }
+    }
+
+    #[test]
+    fn parses_every_kind_of_node() {
+        let template = Template::parse("[{}] {scope}: {b} {[]}").unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![
+                Node::Literal("[".to_owned()),
+                Node::Positional(placeholder("", 2)),
+                Node::Literal("] ".to_owned()),
+                Node::Named(placeholder("scope", 6)),
+                Node::Literal(": ".to_owned()),
+                Node::BranchNumber(placeholder("b", 15)),
+                Node::Literal(" ".to_owned()),
+                Node::Autocomplete(placeholder("[]", 19)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_escaped_braces_as_literal() {
+        let template = Template::parse("{{}} {{b}} {}").unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![
+                Node::Literal("{} {b} ".to_owned()),
+                Node::Positional(placeholder("", 12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_column_of_unclosed_placeholder() {
+        let err = Template::parse("fix: {scope").unwrap_err();
+        assert_eq!(err.column, 6);
+    }
+
+    #[test]
+    fn reports_column_of_unmatched_closing_brace() {
+        let err = Template::parse("fix} {}").unwrap_err();
+        assert_eq!(err.column, 4);
+    }
+
+    #[test]
+    fn reports_column_of_invalid_placeholder() {
+        let err = Template::parse("{} - {not valid}").unwrap_err();
+        assert_eq!(err.column, 6);
+        assert!(err
+            .to_string()
+            .contains("Invalid placeholder `{not valid}`"));
+    }
+
+    #[test]
+    fn parses_filters_in_order() {
+        let template = Template::parse("{title|slug|truncate:50}").unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![Node::Named(Placeholder {
+                name: "title".to_owned(),
+                column: 1,
+                filters: vec![Filter::Slug, Filter::Truncate(50)],
+                optional: false,
+                default: None,
+            })]
+        );
+    }
+
+    #[test]
+    fn reports_column_of_unknown_filter() {
+        let err = Template::parse("{} {title|slug|slg}").unwrap_err();
+        assert_eq!(err.column, 16);
+        assert!(err.message.contains("Unknown filter `slg`"));
+    }
+
+    #[test]
+    fn parses_optional_and_default_placeholders() {
+        let template = Template::parse("{type=chore}: {?} {msg?|upper}").unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![
+                Node::Named(Placeholder {
+                    default: Some("chore".to_owned()),
+                    ..placeholder("type", 1)
+                }),
+                Node::Literal(": ".to_owned()),
+                Node::Positional(Placeholder {
+                    optional: true,
+                    ..placeholder("", 15)
+                }),
+                Node::Literal(" ".to_owned()),
+                Node::Named(Placeholder {
+                    optional: true,
+                    filters: vec![Filter::Upper],
+                    ..placeholder("msg", 19)
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_sections() {
+        let template = Template::parse("fix{? ({scope})}: {}").unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![
+                Node::Literal("fix".to_owned()),
+                Node::Section(Section {
+                    nodes: vec![
+                        Node::Literal(" (".to_owned()),
+                        Node::Named(placeholder("scope", 8)),
+                        Node::Literal(")".to_owned()),
+                    ],
+                    column: 4,
+                }),
+                Node::Literal(": ".to_owned()),
+                Node::Positional(placeholder("", 19)),
+            ]
+        );
+    }
+
+    #[test]
+    fn wraps_optional_placeholder_in_brackets_into_section() {
+        let template = Template::parse("feat({scope?}): {}").unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![
+                Node::Literal("feat".to_owned()),
+                Node::Section(Section {
+                    nodes: vec![
+                        Node::Literal("(".to_owned()),
+                        Node::Named(Placeholder {
+                            optional: true,
+                            ..placeholder("scope", 6)
+                        }),
+                        Node::Literal(")".to_owned()),
+                    ],
+                    column: 5,
+                }),
+                Node::Literal(": ".to_owned()),
+                Node::Positional(placeholder("", 17)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_column_of_unclosed_section() {
+        let err = Template::parse("fix{? ({scope}): {}").unwrap_err();
+        assert_eq!(err.column, 4);
+    }
+
+    #[test]
+    fn parses_built_in_variables() {
+        let template = Template::parse("{date:%Y} {user.name?}").unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![
+                Node::Context(
+                    ContextVariable::Date(Some("%Y".to_owned())),
+                    placeholder("date", 1)
+                ),
+                Node::Literal(" ".to_owned()),
+                Node::Context(
+                    ContextVariable::UserName,
+                    Placeholder {
+                        optional: true,
+                        ..placeholder("user.name", 11)
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_misspelled_built_in_variables() {
+        let err = Template::parse("{} by {user.nmae}").unwrap_err();
+        assert_eq!(err.column, 7);
+        assert!(err
+            .message
+            .contains("Unknown built-in variable `user.nmae`"));
+
+        let err = Template::parse("{} {summary:%Y}").unwrap_err();
+        assert_eq!(err.column, 4);
+    }
+
+    #[test]
+    fn reports_column_of_nested_brace() {
+        let err = Template::parse("{a{b}}").unwrap_err();
+        assert_eq!(err.column, 3);
+    }
+}
@@ -0,0 +1,377 @@
+use std::fmt;
+
+use anyhow::{anyhow, Error, Result};
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{InterpolationValues, Node, Template};
+use crate::existing_branch::ExistingBranchAction;
+
+/// What a place to interpolate means, keyed by its name,
+/// or by position `1`, `2`, ... for `{}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TemplateArg {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<String>,
+    /// Regex the whole value has to match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation: Option<String>,
+}
+
+/// Parts of commit template are rendered as one template joined with it,
+/// so places are shared and `{}` are filled in order across all parts
+pub const PART_SEPARATOR: char = '\u{1e}';
+
+/// Commit or branch template with metadata shown in `list` and `--help`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredVariant")]
+pub struct TemplateVariant {
+    /// Whole branch name, or subject of commit
+    pub template: String,
+    /// Commit body, wrapped to commit_body_width
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Commit trailers like `Refs: {ticket}`, those rendered empty are left out
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub trailers: IndexMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub args: IndexMap<String, TemplateArg>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Branch templates only, overrides existing_branch of config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub existing_branch: Option<ExistingBranchAction>,
+}
+
+/// Bare string is still accepted, e.g. in repo config written by hand
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredVariant {
+    Template(String),
+    Object(Box<VariantObject>),
+}
+
+#[derive(Deserialize)]
+struct VariantObject {
+    template: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    trailers: IndexMap<String, String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    args: IndexMap<String, TemplateArg>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    existing_branch: Option<ExistingBranchAction>,
+}
+
+impl From<StoredVariant> for TemplateVariant {
+    fn from(stored: StoredVariant) -> Self {
+        match stored {
+            StoredVariant::Template(template) => TemplateVariant::new(template),
+            StoredVariant::Object(object) => {
+                let VariantObject {
+                    template,
+                    body,
+                    trailers,
+                    description,
+                    args,
+                    tags,
+                    existing_branch,
+                } = *object;
+                TemplateVariant {
+                    template,
+                    body,
+                    trailers,
+                    description,
+                    args,
+                    tags,
+                    existing_branch,
+                }
+            }
+        }
+    }
+}
+
+impl From<&str> for TemplateVariant {
+    fn from(template: &str) -> Self {
+        TemplateVariant::new(template.to_owned())
+    }
+}
+
+impl TemplateVariant {
+    pub fn new(template: String) -> Self {
+        TemplateVariant {
+            template,
+            ..Default::default()
+        }
+    }
+
+    /// Template, body and trailer values in order
+    fn parts(&self) -> Vec<&str> {
+        let mut parts = vec![self.template.as_str()];
+        parts.extend(self.body.as_deref());
+        parts.extend(self.trailers.values().map(|value| value.as_str()));
+        parts
+    }
+
+    /// Each part is parsed on its own first, so errors point to column in it
+    pub fn parse(&self) -> Result<Template> {
+        for part in self.parts() {
+            Template::parse(part)?;
+        }
+        Ok(Template::parse(
+            &self.parts().join(&PART_SEPARATOR.to_string()),
+        )?)
+    }
+
+    /// Arg keys that template has a place for: names, built-ins that can be
+    /// given as name=value and then `{}` positions
+    pub fn arg_keys(template: &Template) -> Vec<String> {
+        let mut keys: Vec<String> = vec![];
+        for (node, _) in template.placeholders() {
+            if let Node::Named(placeholder) | Node::Context(_, placeholder) = node {
+                if !keys.contains(&placeholder.name) {
+                    keys.push(placeholder.name.to_owned());
+                }
+            }
+        }
+        keys.extend((1..=template.positional_count()).map(|position| position.to_string()));
+        keys
+    }
+
+    /// Checks args definitions against template, so typos don't go unnoticed
+    pub fn validate_args(&self) -> Result<()> {
+        let keys = Self::arg_keys(&self.parse()?);
+        let mut problems = vec![];
+        for (key, arg) in &self.args {
+            if !keys.contains(key) {
+                problems.push(format!(
+                    "Template has no place for arg `{}`, it has: {}",
+                    key,
+                    keys.join(", ")
+                ));
+            }
+            if let Some(validation) = &arg.validation {
+                if let Err(err) = full_match_regex(validation) {
+                    problems.push(format!("Invalid validation of `{}`: {}", key, err));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::msg(problems.join("\n")))
+        }
+    }
+
+    /// Value given for arg under key, named values are looked up by name
+    /// and `{}` values by position
+    fn value_of<'a>(key: &str, values: &'a InterpolationValues) -> Option<&'a String> {
+        match key.parse::<usize>() {
+            Ok(position) if position > 0 => values.positional.get(position - 1),
+            _ => values.named.get(key),
+        }
+    }
+
+    /// Checks value against validation regex of arg under key, if it has one
+    pub fn validate_value(&self, key: &str, value: &str) -> Result<()> {
+        let Some(arg) = self.args.get(key) else {
+            return Ok(());
+        };
+        let Some(validation) = &arg.validation else {
+            return Ok(());
+        };
+        if full_match_regex(validation)?.is_match(value) {
+            return Ok(());
+        }
+
+        let mut problem = format!(
+            "Value `{}` of {} doesn't match {}",
+            value,
+            arg_label(key),
+            validation
+        );
+        if let Some(example) = &arg.example {
+            problem.push_str(&format!(", e.g. {}", example));
+        }
+        Err(Error::msg(problem))
+    }
+
+    /// Regex matching subjects the template renders to, values of args
+    /// have to match their validation
+    pub fn subject_matcher(&self) -> Result<Regex> {
+        Template::parse(&self.template)?
+            .matcher(&|key| self.args.get(key).and_then(|arg| arg.validation.to_owned()))
+    }
+
+    /// Runs validation regexes of args against given values
+    pub fn validate_values(&self, values: &InterpolationValues) -> Result<()> {
+        let problems: Vec<String> = self
+            .args
+            .keys()
+            .filter_map(|key| {
+                let value = Self::value_of(key, values)?;
+                self.validate_value(key, value).err()
+            })
+            .map(|err| err.to_string())
+            .collect();
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::msg(format!("\n {}", problems.join("\n "))))
+        }
+    }
+
+    /// Lines describing template, its args and tags, without the template itself
+    pub fn details(&self) -> Vec<String> {
+        let mut lines = vec![];
+        if let Some(description) = &self.description {
+            lines.push(description.to_owned());
+        }
+        if let Some(body) = &self.body {
+            lines.push(format!("body: {}", body.replace('\n', "\\n")));
+        }
+        for (key, value) in &self.trailers {
+            lines.push(format!("trailer {}: {}", key, value));
+        }
+        for (key, arg) in &self.args {
+            let mut line = arg_label(key);
+            if let Some(prompt) = &arg.prompt {
+                line.push_str(&format!(": {}", prompt));
+            }
+            if let Some(example) = &arg.example {
+                line.push_str(&format!(" (e.g. {})", example));
+            }
+            if let Some(validation) = &arg.validation {
+                line.push_str(&format!(" [{}]", validation));
+            }
+            lines.push(line);
+        }
+        if !self.tags.is_empty() {
+            lines.push(format!("tags: {}", self.tags.join(", ")));
+        }
+        if let Some(action) = self.existing_branch {
+            lines.push(format!("existing branch: {}", action));
+        }
+        lines
+    }
+}
+
+/// `{summary}` for named arg and `{} #1` for positional one
+pub fn arg_label(key: &str) -> String {
+    match key.parse::<usize>() {
+        Ok(_) => format!("{{}} #{}", key),
+        Err(_) => format!("{{{}}}", key),
+    }
+}
+
+fn full_match_regex(validation: &str) -> Result<Regex> {
+    Regex::new(&format!("^(?:{})$", validation))
+        .map_err(|err| anyhow!("Invalid validation regex {}: {}", validation, err))
+}
+
+/// Template followed by indented details
+impl fmt::Display for TemplateVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.template)?;
+        for line in self.details() {
+            write!(f, "\n    {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Section of `--help` listing templates stored in config
+pub fn templates_help(title: &str, variants: &IndexMap<String, TemplateVariant>) -> String {
+    if variants.is_empty() {
+        return format!("{}: none are set yet", title);
+    }
+    let mut help = format!("{}:", title);
+    for (key, variant) in variants {
+        help.push_str(&format!("\n  {}: {}", key, variant.template));
+        for line in variant.details() {
+            help.push_str(&format!("\n      {}", line));
+        }
+    }
+    help
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variant() -> TemplateVariant {
+        serde_json::from_str(
+            r#"{
+                "template": "[{ticket}] {}",
+                "description": "Commit for ticket",
+                "args": {
+                    "ticket": {"prompt": "Ticket id", "example": "PAY-12", "validation": "[A-Z]+-\\d+"},
+                    "1": {"prompt": "What changed"}
+                },
+                "tags": ["jira"]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn reads_bare_string_and_writes_object() {
+        let variant: TemplateVariant = serde_json::from_str(r#""{} - {}""#).unwrap();
+        assert_eq!(variant, TemplateVariant::from("{} - {}"));
+        assert_eq!(
+            serde_json::to_string(&variant).unwrap(),
+            r#"{"template":"{} - {}"}"#
+        );
+    }
+
+    #[test]
+    fn validates_values_with_full_match() {
+        let variant = variant();
+        variant.validate_args().unwrap();
+
+        let values: InterpolationValues = vec!["ticket=PAY-12".to_owned(), "fix".to_owned()].into();
+        variant.validate_values(&values).unwrap();
+
+        let values: InterpolationValues =
+            vec!["ticket=xPAY-12".to_owned(), "fix".to_owned()].into();
+        assert_eq!(
+            variant.validate_values(&values).unwrap_err().to_string(),
+            "\n Value `xPAY-12` of {ticket} doesn't match [A-Z]+-\\d+, e.g. PAY-12"
+        );
+    }
+
+    #[test]
+    fn rejects_args_template_has_no_place_for() {
+        let mut variant = variant();
+        variant.args.insert("2".to_owned(), TemplateArg::default());
+        assert_eq!(
+            variant.validate_args().unwrap_err().to_string(),
+            "Template has no place for arg `2`, it has: ticket, 1"
+        );
+    }
+
+    #[test]
+    fn displays_metadata_below_template() {
+        assert_eq!(
+            variant().to_string(),
+            "[{ticket}] {}\n    \
+            Commit for ticket\n    \
+            {ticket}: Ticket id (e.g. PAY-12) [[A-Z]+-\\d+]\n    \
+            {} #1: What changed\n    \
+            tags: jira"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::{fmt, process::Command};
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::{branch_name::get_branch_captures, git, git_config::Data};
+
+/// Used when no ticket keys are configured, matches e.g. PAY-4821
+const DEFAULT_TICKET_PATTERN: &str = r"(?P<key>[A-Z][A-Z0-9]+)-(?P<number>\d+)";
+
+/// Name of branch pattern group that's taken as a ticket
+const TICKET_GROUP: &str = "ticket";
+
+#[derive(Debug, PartialEq)]
+pub struct Ticket {
+    pub key: String,
+    pub number: String,
+}
+
+impl fmt::Display for Ticket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            return write!(f, "{}", self.number);
+        }
+        write!(f, "{}-{}", self.key, self.number)
+    }
+}
+
+impl Ticket {
+    pub fn from_id(id: &str) -> Ticket {
+        match id.rsplit_once('-') {
+            Some((key, number)) => Ticket {
+                key: key.to_uppercase(),
+                number: number.to_owned(),
+            },
+            None => Ticket {
+                key: String::new(),
+                number: id.to_owned(),
+            },
+        }
+    }
+}
+
+fn is_project_key(ticket_key: &str) -> bool {
+    Regex::new(r"^[A-Za-z][A-Za-z0-9_]*$")
+        .unwrap()
+        .is_match(ticket_key)
+}
+
+/// Each entry is either a project key like `PAY` or a regex matching whole ticket,
+/// optionally with `key` and `number` groups
+pub fn ticket_patterns(ticket_keys: &[String]) -> Result<Vec<Regex>> {
+    if ticket_keys.is_empty() {
+        return Ok(vec![Regex::new(DEFAULT_TICKET_PATTERN).unwrap()]);
+    }
+
+    ticket_keys
+        .iter()
+        .map(|ticket_key| {
+            let pattern = if is_project_key(ticket_key) {
+                format!(
+                    r"(?i)(?:^|[^a-z0-9])(?P<key>{})-(?P<number>\d+)",
+                    regex::escape(ticket_key)
+                )
+            } else {
+                ticket_key.to_owned()
+            };
+            Ok(Regex::new(&pattern)?)
+        })
+        .collect()
+}
+
+pub fn find_ticket(text: &str, patterns: &[Regex]) -> Option<Ticket> {
+    patterns.iter().find_map(|pattern| {
+        let captures = pattern.captures(text)?;
+        match (captures.name("key"), captures.name("number")) {
+            (Some(key), Some(number)) => Some(Ticket {
+                key: key.as_str().to_uppercase(),
+                number: number.as_str().to_owned(),
+            }),
+            _ => Some(Ticket::from_id(captures.get(0)?.as_str())),
+        }
+    })
+}
+
+/// Looks for ticket in current branch name, falls back to clipboard only
+/// when asked to. Invalid patterns are skipped with a warning
+pub fn find_current_ticket(data: &Data, from_clipboard: bool) -> Option<Ticket> {
+    let patterns = ticket_patterns(data.ticket_keys.as_deref().unwrap_or_default())
+        .map_err(|err| eprintln!("Ticket keys are skipped, they are invalid: {}", err))
+        .unwrap_or_default();
+
+    if let Ok(branch_name) = git::current_branch() {
+        let branch_pattern = data.branch_pattern.as_ref().and_then(|branch_pattern| {
+            Regex::new(branch_pattern)
+                .map_err(|err| {
+                    eprintln!(
+                        "Branch pattern {} is skipped, it's invalid: {}",
+                        branch_pattern, err
+                    )
+                })
+                .ok()
+        });
+        if let Some(branch_pattern) = branch_pattern {
+            if let Some(ticket) =
+                get_branch_captures(&branch_name, &branch_pattern).get(TICKET_GROUP)
+            {
+                return Some(Ticket::from_id(ticket));
+            }
+        }
+        if let Some(ticket) = find_ticket(&branch_name, &patterns) {
+            return Some(ticket);
+        }
+    }
+
+    if !from_clipboard {
+        return None;
+    }
+    let clipboard = Command::new(&data.clipboard_commands.paste).output().ok()?;
+    find_ticket(&String::from_utf8_lossy(&clipboard.stdout), &patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket(key: &str, number: &str) -> Ticket {
+        Ticket {
+            key: key.to_owned(),
+            number: number.to_owned(),
+        }
+    }
+
+    #[test]
+    fn finds_ticket_with_default_pattern() {
+        let patterns = ticket_patterns(&[]).unwrap();
+        assert_eq!(
+            find_ticket("feature/PAY-4821-fix", &patterns),
+            Some(ticket("PAY", "4821"))
+        );
+        assert_eq!(find_ticket("feature/v2-1234-fix", &patterns), None);
+    }
+
+    #[test]
+    fn finds_ticket_with_project_keys() {
+        let patterns = ticket_patterns(&["PAY".to_owned(), "OPS".to_owned()]).unwrap();
+        assert_eq!(
+            find_ticket("fix/ops-12-deploy", &patterns),
+            Some(ticket("OPS", "12"))
+        );
+        assert_eq!(find_ticket("fix/XPAY-12", &patterns), None);
+        assert_eq!(find_ticket("fix/ABC-12", &patterns), None);
+    }
+
+    #[test]
+    fn finds_ticket_with_custom_pattern() {
+        let patterns = ticket_patterns(&[r"#\d+".to_owned()]).unwrap();
+        assert_eq!(
+            find_ticket("fix/#12-deploy", &patterns),
+            Some(ticket("", "#12"))
+        );
+
+        let patterns = ticket_patterns(&[r"(?P<key>gh)(?P<number>\d+)".to_owned()]).unwrap();
+        assert_eq!(find_ticket("fix/gh12", &patterns), Some(ticket("GH", "12")));
+    }
+
+    #[test]
+    fn displays_ticket_as_id() {
+        assert_eq!(Ticket::from_id("pay-4821").to_string(), "PAY-4821");
+    }
+}